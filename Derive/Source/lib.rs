@@ -0,0 +1,327 @@
+#![allow(non_snake_case)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Expr, Fields, Ident, LitInt, Result, Type};
+
+/// Variants of `Graphics::Render::VertexFormat` accepted by `#[vertex(format = ...)]`.
+const FORMATS: &[&str] = &[
+    "UInt8x2",
+    "UInt8x4",
+    "Int8x2",
+    "Int8x4",
+    "UNorm8x2",
+    "UNorm8x4",
+    "Norm8x2",
+    "Norm8x4",
+    "UInt16x2",
+    "UInt16x4",
+    "Int16x2",
+    "Int16x4",
+    "UNorm16x2",
+    "UNorm16x4",
+    "Norm16x2",
+    "Norm16x4",
+    "Float16x2",
+    "Float16x4",
+    "Float32",
+    "Float32x2",
+    "Float32x3",
+    "Float32x4",
+    "UInt32",
+    "UInt32x2",
+    "UInt32x3",
+    "UInt32x4",
+    "Int32",
+    "Int32x2",
+    "Int32x3",
+    "Int32x4",
+    "Float64",
+    "Float64x2",
+    "Float64x3",
+    "Float64x4",
+];
+
+/// Implements `Graphics::Render::Vertex` for a `#[repr(C)]` struct.
+///
+/// Formats are picked from the field types, offsets and stride come from the struct
+/// layout and shader locations are assigned in field order.
+///
+/// ```text
+/// #[repr(C)]
+/// #[derive(Copy, Clone, Pod, Zeroable, Vertex)]
+/// #[vertex(step_mode = Instance, location = 5)]
+/// struct InstanceRaw {
+///     model: [[f32; 4]; 4],            // locations 5..8, one Float32x4 per row
+///     #[vertex(format = UNorm8x4)]
+///     color: [u8; 4],                  // location 9
+///     #[vertex(location = 12)]
+///     scale: f32,                      // location 12
+///     #[vertex(skip)]
+///     padding: [u32; 3],
+/// }
+/// ```
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn DeriveVertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    ExpandVertex(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn ExpandVertex(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "#[derive(Vertex)] does not support generic structs",
+        ));
+    }
+
+    if !IsReprC(input)? {
+        return Err(Error::new_spanned(
+            name,
+            "#[derive(Vertex)] requires #[repr(C)] so the field offsets are stable",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    name,
+                    "#[derive(Vertex)] requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "#[derive(Vertex)] can only be used on structs",
+            ))
+        }
+    };
+
+    let mut step_mode = format_ident!("Vertex");
+    let mut location = 0u32;
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("vertex")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("step_mode") {
+                step_mode = meta.value()?.parse::<Ident>()?;
+
+                if step_mode != "Vertex" && step_mode != "Instance" {
+                    return Err(Error::new_spanned(
+                        &step_mode,
+                        "step_mode must be `Vertex` or `Instance`",
+                    ));
+                }
+
+                Ok(())
+            } else if meta.path.is_ident("location") {
+                location = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("expected `step_mode` or `location`"))
+            }
+        })?;
+    }
+
+    let mut attributes = Vec::new();
+    let mut size_checks = Vec::new();
+
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let mut format = None;
+        let mut skip = false;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("vertex")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("location") {
+                    location = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+                    Ok(())
+                } else if meta.path.is_ident("format") {
+                    let ident = meta.value()?.parse::<Ident>()?;
+
+                    if !FORMATS.iter().any(|name| ident == name) {
+                        return Err(Error::new_spanned(
+                            &ident,
+                            format!("unknown vertex format `{}`", ident),
+                        ));
+                    }
+
+                    format = Some(ident);
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `location`, `format` or `skip`"))
+                }
+            })?;
+        }
+
+        if skip {
+            continue;
+        }
+
+        // Matrices like `[[f32; 4]; 4]` take one location per row
+        let (element, count) = match &field.ty {
+            Type::Array(array) if matches!(&*array.elem, Type::Array(_)) => {
+                (&*array.elem, ParseLength(&array.len)?)
+            }
+            ty => (ty, 1),
+        };
+
+        let format = match format {
+            Some(format) => format,
+            None => GetFormat(element).ok_or_else(|| {
+                Error::new(
+                    field.ty.span(),
+                    format!(
+                        "unsupported vertex field type `{}`, specify it with #[vertex(format = ...)]",
+                        quote!(#element)
+                    ),
+                )
+            })?,
+        };
+
+        let message = format!(
+            "field `{}` does not match the size of VertexFormat::{}",
+            field_name, format
+        );
+
+        size_checks.push(quote! {
+            ::core::assert!(
+                ::core::mem::size_of::<#element>() as u64
+                    == ::Graphics::Render::VertexFormat::#format.GetSize(),
+                #message
+            );
+        });
+
+        for i in 0..count {
+            let label = if count == 1 {
+                field_name.to_string()
+            } else {
+                format!("{}{}", field_name, i)
+            };
+
+            attributes.push(quote! {
+                ::Graphics::Render::VertexAttribute {
+                    label: ::std::borrow::Cow::Borrowed(#label),
+                    format: ::Graphics::Render::VertexFormat::#format,
+                    offset: ::core::mem::offset_of!(#name, #field_name)
+                        + #i * ::core::mem::size_of::<#element>(),
+                    shader_location: #location,
+                }
+            });
+
+            location += 1;
+        }
+    }
+
+    let label = name.to_string();
+
+    Ok(quote! {
+        const _: () = {
+            #(#size_checks)*
+        };
+
+        impl ::Graphics::Render::Vertex for #name {
+            fn GetLayout() -> ::Graphics::Render::VertexBufferLayout {
+                ::Graphics::Render::VertexBufferLayout {
+                    label: ::std::borrow::Cow::Borrowed(#label),
+                    stride: ::core::mem::size_of::<#name>(),
+                    step_mode: ::Graphics::Render::StepMode::#step_mode,
+                    attributes: ::std::vec![#(#attributes),*],
+                }
+            }
+        }
+    })
+}
+
+fn IsReprC(input: &DeriveInput) -> Result<bool> {
+    let mut is_repr_c = false;
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                is_repr_c = true;
+            }
+
+            // Skip arguments of other representations like `align(16)`
+            if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<TokenStream2>()?;
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(is_repr_c)
+}
+
+fn ParseLength(len: &Expr) -> Result<usize> {
+    match len {
+        Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(int),
+            ..
+        }) => int.base10_parse(),
+        _ => Err(Error::new_spanned(
+            len,
+            "array lengths of vertex fields must be integer literals",
+        )),
+    }
+}
+
+/// Maps a field type to the matching `VertexFormat` variant.
+fn GetFormat(ty: &Type) -> Option<Ident> {
+    let (scalar, count) = match ty {
+        Type::Path(path) => (path.path.get_ident()?.to_string(), 1),
+        Type::Array(array) => match &*array.elem {
+            Type::Path(path) => (
+                path.path.get_ident()?.to_string(),
+                ParseLength(&array.len).ok()?,
+            ),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let format = match (scalar.as_str(), count) {
+        ("u8", 2) => "UInt8x2",
+        ("u8", 4) => "UInt8x4",
+        ("i8", 2) => "Int8x2",
+        ("i8", 4) => "Int8x4",
+        ("u16", 2) => "UInt16x2",
+        ("u16", 4) => "UInt16x4",
+        ("i16", 2) => "Int16x2",
+        ("i16", 4) => "Int16x4",
+        ("f32", 1) => "Float32",
+        ("f32", 2) => "Float32x2",
+        ("f32", 3) => "Float32x3",
+        ("f32", 4) => "Float32x4",
+        ("u32", 1) => "UInt32",
+        ("u32", 2) => "UInt32x2",
+        ("u32", 3) => "UInt32x3",
+        ("u32", 4) => "UInt32x4",
+        ("i32", 1) => "Int32",
+        ("i32", 2) => "Int32x2",
+        ("i32", 3) => "Int32x3",
+        ("i32", 4) => "Int32x4",
+        ("f64", 1) => "Float64",
+        ("f64", 2) => "Float64x2",
+        ("f64", 3) => "Float64x3",
+        ("f64", 4) => "Float64x4",
+        _ => return None,
+    };
+
+    Some(format_ident!("{}", format))
+}
@@ -0,0 +1,219 @@
+use anyhow::Result;
+use cgmath::prelude::*;
+use std::path::Path;
+use std::time::Duration;
+use wgpu::util::DeviceExt;
+use winit::event::*;
+use Graphics::Camera::*;
+use Graphics::Render::*;
+use Graphics::Shader::*;
+use Graphics::State;
+
+pub struct Cubes {
+    render_pipeline: PipelineHandle,
+    obj_model: Model,
+    camera: Camera,
+    camera_controller: CameraController,
+    camera_uniform: CameraUniform,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    instances: Vec<Instance>,
+    instance_buffer: wgpu::Buffer,
+    depth_texture: Texture,
+}
+
+impl State for Cubes {
+    fn Init(renderer: &Renderer) -> Result<Self> {
+        // Shader
+
+        let shader =
+            Shader::FromFile(Path::new(env!("CARGO_MANIFEST_DIR")).join("Shaders/Cubes.wgsl"))?;
+
+        // Group 0 holds the diffuse texture and sampler, group 1 the camera
+        let layouts = renderer.CreateReflectedLayouts("Cubes", &shader.Reflect()?);
+        let texture_bind_group_layout = layouts.bind_group_layouts[0].Get();
+        let camera_bind_group_layout = layouts.bind_group_layouts[1].Get();
+
+        // Camera
+
+        let camera = Camera {
+            eye: (0.0, 5.0, -10.0).into(),
+            target: (0.0, 0.0, 0.0).into(),
+            up: cgmath::Vector3::unit_y(),
+            aspect: renderer.config.width as f32 / renderer.config.height as f32,
+            fovy: 45.0,
+            znear: 0.1,
+            zfar: 100.0,
+        };
+
+        let camera_controller = CameraController::New(0.2);
+
+        let mut camera_uniform = CameraUniform::New();
+        camera_uniform.UpdateViewProjection(&camera);
+
+        let camera_buffer = renderer
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Camera Buffer"),
+                contents: bytemuck::cast_slice(&[camera_uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+
+        const SPACE_BETWEEN: f32 = 3.0;
+        let instances = (0..NUM_INSTANCES_PER_ROW)
+            .flat_map(|z| {
+                (0..NUM_INSTANCES_PER_ROW).map(move |x| {
+                    let x = SPACE_BETWEEN * (x as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+                    let z = SPACE_BETWEEN * (z as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+
+                    let position = cgmath::Vector3 { x, y: 0.0, z };
+
+                    let rotation = if position.is_zero() {
+                        cgmath::Quaternion::from_axis_angle(
+                            cgmath::Vector3::unit_z(),
+                            cgmath::Deg(0.0),
+                        )
+                    } else {
+                        cgmath::Quaternion::from_axis_angle(position.normalize(), cgmath::Deg(45.0))
+                    };
+
+                    Instance { position, rotation }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let instance_data = instances.iter().map(Instance::ToRaw).collect::<Vec<_>>();
+        let instance_buffer =
+            renderer
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Instance Buffer"),
+                    contents: bytemuck::cast_slice(&instance_data),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+
+        let camera_bind_group = renderer
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: camera_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                }],
+                label: Some("camera_bind_group"),
+            });
+
+        // Model
+
+        let obj_model = Model::Load(
+            &renderer.device,
+            &renderer.queue,
+            &renderer.mipmap_generators,
+            texture_bind_group_layout,
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("Content/SM_Cube.obj"),
+        )?;
+
+        // Texture
+
+        let depth_texture =
+            Texture::CreateDepthTexture(&renderer.device, &renderer.config, "depth_texture");
+
+        // Pipeline
+
+        let render_pipeline = renderer.GetPipeline(
+            &PipelineDescriptor {
+                label: "Render Pipeline".into(),
+                vertex_layouts: vec![ModelVertex::GetLayout(), InstanceRaw::GetLayout()],
+                cull_mode: CullMode::Back,
+                depth_stencil: Some(DepthStencilState::default()),
+                ..PipelineDescriptor::New(shader)
+            },
+            &layouts.GetBindGroupLayouts(),
+        )?;
+
+        Ok(Self {
+            render_pipeline,
+            obj_model,
+            camera,
+            camera_controller,
+            camera_buffer,
+            camera_bind_group,
+            camera_uniform,
+            instances,
+            instance_buffer,
+            depth_texture,
+        })
+    }
+
+    fn Input(&mut self, renderer: &Renderer, event: &WindowEvent) -> bool {
+        self.camera_controller.ProcessEvents(event)
+    }
+
+    fn Update(&mut self, renderer: &Renderer, delta: Duration) {
+        self.camera_controller.UpdateCamera(&mut self.camera);
+        self.camera_uniform.UpdateViewProjection(&self.camera);
+        renderer.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[self.camera_uniform]),
+        );
+    }
+
+    fn Resize(&mut self, renderer: &Renderer) {
+        self.depth_texture =
+            Texture::CreateDepthTexture(&renderer.device, &renderer.config, "depth_texture");
+    }
+
+    fn Draw(&mut self, renderer: &mut Renderer) -> Result<(), RendererError> {
+        let frame = renderer.GetCurrentFrame()?;
+
+        let mut encoder = renderer
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        // Fetched every frame, hot reloading replaces the pipeline behind the handle
+        let render_pipeline = self.render_pipeline.Get();
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &frame.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.1,
+                            g: 0.2,
+                            b: 0.3,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_texture.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+
+            render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+            render_pass.set_pipeline(&render_pipeline);
+            render_pass.DrawModelInstanced(
+                &self.obj_model,
+                0..self.instances.len() as u32,
+                &self.camera_bind_group,
+            );
+        }
+
+        renderer.queue.submit(std::iter::once(encoder.finish()));
+        frame.Present();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use bytemuck::{Pod, Zeroable};
+use std::path::Path;
+use std::time::Duration;
+use winit::event::*;
+use Graphics::Render::*;
+use Graphics::Shader::*;
+use Graphics::State;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable, Vertex)]
+struct TriangleVertex {
+    position: [f32; 3],
+    color: [f32; 3],
+}
+
+#[rustfmt::skip]
+const VERTICES: &[TriangleVertex] = &[
+    TriangleVertex { position: [0.0, 0.5, 0.0], color: [1.0, 0.0, 0.0] },
+    TriangleVertex { position: [-0.5, -0.5, 0.0], color: [0.0, 1.0, 0.0] },
+    TriangleVertex { position: [0.5, -0.5, 0.0], color: [0.0, 0.0, 1.0] },
+];
+
+#[rustfmt::skip]
+const INDICES: &[u16] = &[
+    0, 1, 2,
+];
+
+pub struct Triangle {
+    render_pipeline: PipelineHandle,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_format: IndexFormat,
+    num_indices: u32,
+}
+
+impl State for Triangle {
+    fn Init(renderer: &Renderer) -> Result<Self> {
+        // Pipeline
+
+        let render_pipeline = renderer.GetPipeline(
+            &PipelineDescriptor {
+                label: "RENDER_PIPELINE".into(),
+                vertex_layouts: vec![TriangleVertex::GetLayout()],
+                cull_mode: CullMode::Back,
+                ..PipelineDescriptor::New(Shader::FromFile(
+                    Path::new(env!("CARGO_MANIFEST_DIR")).join("Shaders/Triangle.wgsl"),
+                )?)
+            },
+            &[],
+        )?;
+
+        let vertex_buffer = renderer.SubmitVertexBuffer(&VertexBuffer {
+            label: "Vertex Buffer".into(),
+            content: bytemuck::cast_slice(VERTICES).to_vec(),
+        });
+
+        let indices = IndexBuffer::FromU16("Index Buffer", INDICES);
+        let index_buffer = renderer.SubmitIndexBuffer(&indices);
+        let index_format = indices.format;
+        let num_indices = indices.GetLength();
+
+        Ok(Self {
+            render_pipeline,
+            vertex_buffer,
+            index_buffer,
+            index_format,
+            num_indices,
+        })
+    }
+
+    fn Input(&mut self, renderer: &Renderer, event: &WindowEvent) -> bool {
+        false
+    }
+
+    fn Update(&mut self, renderer: &Renderer, delta: Duration) {}
+
+    fn Resize(&mut self, renderer: &Renderer) {}
+
+    fn Draw(&mut self, renderer: &mut Renderer) -> Result<(), RendererError> {
+        renderer.Draw(
+            &self.render_pipeline.Get(),
+            &self.vertex_buffer,
+            &self.index_buffer,
+            self.index_format,
+            self.num_indices,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn DerivedVertexLayoutMatchesFields() {
+        let layout = TriangleVertex::GetLayout();
+
+        assert_eq!(layout.stride, 24);
+        assert_eq!(layout.step_mode, StepMode::Vertex);
+        assert_eq!(
+            layout
+                .attributes
+                .iter()
+                .map(|a| (a.format, a.offset, a.shader_location))
+                .collect::<Vec<_>>(),
+            vec![
+                (VertexFormat::Float32x3, 0, 0),
+                (VertexFormat::Float32x3, 12, 1),
+            ]
+        );
+    }
+}
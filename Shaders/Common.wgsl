@@ -0,0 +1,22 @@
+// Vertex structs shared by the example shaders.
+//
+// Define VERTEX_COLOR before including this file to pass a color per vertex
+// instead of texture coordinates.
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+#ifdef VERTEX_COLOR
+    @location(1) color: vec3<f32>,
+#else
+    @location(1) tex_coords: vec2<f32>,
+#endif
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+#ifdef VERTEX_COLOR
+    @location(0) color: vec3<f32>,
+#else
+    @location(0) tex_coords: vec2<f32>,
+#endif
+};
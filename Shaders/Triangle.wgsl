@@ -1,16 +1,9 @@
-// Vertex
-
-struct VertexInput {
-    @location(0) position: vec3<f32>,
-    @location(1) color: vec3<f32>,
-};
+#define VERTEX_COLOR
+#include "Common.wgsl"
 
-struct VertexOutput {
-    @builtin(position) clip_position: vec4<f32>,
-    @location(0) color: vec3<f32>,
-};
+// Vertex
 
-@stage(vertex)
+@vertex
 fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
@@ -22,7 +15,7 @@ fn vs_main(
 
 // Fragment
 
-@stage(fragment)
+@fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return vec4<f32>(in.color, 1.0);
 }
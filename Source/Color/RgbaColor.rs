@@ -15,3 +15,68 @@ pub struct RgbaColor {
     /// Alpha component. [0.0, 1.0]
     pub alpha: f32,
 }
+
+impl RgbaColor {
+    /// Decodes an 8 bit sRGB color, alpha is stored linearly.
+    pub fn FromSrgb8(color: [u8; 4]) -> Self {
+        let [red, green, blue, alpha] = color.map(|value| value as f32 / 255.0);
+
+        Self {
+            red: Self::SrgbToLinear(red),
+            green: Self::SrgbToLinear(green),
+            blue: Self::SrgbToLinear(blue),
+            alpha,
+        }
+    }
+
+    /// Encodes to 8 bit sRGB, components are clamped to [0.0, 1.0].
+    pub fn ToSrgb8(&self) -> [u8; 4] {
+        let encode = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        [
+            encode(Self::LinearToSrgb(self.red.clamp(0.0, 1.0))),
+            encode(Self::LinearToSrgb(self.green.clamp(0.0, 1.0))),
+            encode(Self::LinearToSrgb(self.blue.clamp(0.0, 1.0))),
+            encode(self.alpha),
+        ]
+    }
+
+    pub fn SrgbToLinear(value: f32) -> f32 {
+        if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    pub fn LinearToSrgb(value: f32) -> f32 {
+        if value <= 0.0031308 {
+            value * 12.92
+        } else {
+            1.055 * value.powf(1.0 / 2.4) - 0.055
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn Srgb8RoundTrips() {
+        for value in 0..=255 {
+            let color = [value, value, value, value];
+            assert_eq!(RgbaColor::FromSrgb8(color).ToSrgb8(), color);
+        }
+    }
+
+    #[test]
+    fn FromSrgb8Linearizes() {
+        let color = RgbaColor::FromSrgb8([188, 0, 255, 128]);
+
+        assert!((color.red - 0.5).abs() < 0.01);
+        assert_eq!(color.green, 0.0);
+        assert_eq!(color.blue, 1.0);
+        assert!((color.alpha - 128.0 / 255.0).abs() < 1e-6);
+    }
+}
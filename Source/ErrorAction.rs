@@ -0,0 +1,30 @@
+use crate::Render::RendererError;
+
+/// How the runtime reacts to a [`RendererError`], returned by [`State::OnError`](crate::State::OnError).
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum ErrorAction {
+    /// Skips the frame and tries again on the next one.
+    Retry,
+
+    /// Reconfigures the surface to the window size, then resizes the state.
+    Reconfigure,
+
+    /// Creates a new device and initializes the state again.
+    RecreateDevice,
+
+    /// Stops the event loop.
+    Exit,
+}
+
+impl ErrorAction {
+    /// Default reaction, used unless a state overrides [`State::OnError`](crate::State::OnError).
+    pub fn FromError(error: &RendererError) -> Self {
+        match error {
+            RendererError::Surface(wgpu::SurfaceError::Lost)
+            | RendererError::Surface(wgpu::SurfaceError::Outdated) => Self::Reconfigure,
+            RendererError::Surface(wgpu::SurfaceError::Timeout) => Self::Retry,
+            RendererError::DeviceLost(_) => Self::RecreateDevice,
+            _ => Self::Exit,
+        }
+    }
+}
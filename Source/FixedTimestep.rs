@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Settings for running [`State::FixedUpdate`](crate::State::FixedUpdate) at a constant rate.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FixedTimestep {
+    /// Fixed updates per second.
+    pub tick_rate: u32,
+
+    /// Maximum fixed updates run in a single frame, remaining time is dropped to
+    /// avoid spiraling when updates are slower than real time. Zero is treated as one.
+    pub max_steps: u32,
+
+    /// Time not yet consumed by fixed updates.
+    #[serde(skip)]
+    accumulator: Duration,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::New(60, 8)
+    }
+}
+
+impl FixedTimestep {
+    pub fn New(tick_rate: u32, max_steps: u32) -> Self {
+        Self {
+            tick_rate,
+            max_steps: max_steps.max(1),
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    /// Duration of one fixed update, at least a nanosecond so rates above 1e9 don't
+    /// round down to zero.
+    pub fn GetStep(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.tick_rate.max(1) as f64).max(Duration::from_nanos(1))
+    }
+
+    /// Adds the frame time and returns how many fixed updates to run, along with the
+    /// fraction of a step left over for interpolating between the last two updates.
+    pub fn Advance(&mut self, delta: Duration) -> (u32, f32) {
+        let step = self.GetStep();
+        let max_steps = self.max_steps.max(1);
+
+        self.accumulator += delta;
+
+        let available = self.accumulator.as_nanos() / step.as_nanos();
+
+        let steps = if available > max_steps as u128 {
+            // Drop the backlog instead of trying to catch up forever
+            self.accumulator =
+                Duration::from_nanos((self.accumulator.as_nanos() % step.as_nanos()) as u64);
+            max_steps
+        } else {
+            self.accumulator -= step * available as u32;
+            available as u32
+        };
+
+        let alpha = self.accumulator.as_secs_f32() / step.as_secs_f32();
+
+        (steps, alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn AdvanceAccumulatesPartialSteps() {
+        let mut fixed_timestep = FixedTimestep::New(10, 8);
+
+        assert_eq!(fixed_timestep.Advance(Duration::from_millis(40)).0, 0);
+        assert_eq!(fixed_timestep.Advance(Duration::from_millis(40)).0, 0);
+
+        let (steps, alpha) = fixed_timestep.Advance(Duration::from_millis(40));
+        assert_eq!(steps, 1);
+        assert!((alpha - 0.2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn AdvanceClampsToMaxSteps() {
+        let mut fixed_timestep = FixedTimestep::New(10, 4);
+
+        let (steps, alpha) = fixed_timestep.Advance(Duration::from_millis(10_050));
+        assert_eq!(steps, 4);
+        assert!((alpha - 0.5).abs() < 1e-4);
+
+        // The backlog was dropped, the next frame does not try to catch up
+        let (steps, _) = fixed_timestep.Advance(Duration::from_millis(100));
+        assert_eq!(steps, 1);
+    }
+
+    #[test]
+    fn AdvanceKeepsAlphaInRange() {
+        let mut fixed_timestep = FixedTimestep::New(60, 8);
+
+        for millis in [0, 1, 7, 16, 17, 33, 100, 250, 1000] {
+            let (steps, alpha) = fixed_timestep.Advance(Duration::from_millis(millis));
+            assert!(steps <= 8);
+            assert!((0.0..1.0).contains(&alpha), "alpha {} out of range", alpha);
+        }
+    }
+
+    #[test]
+    fn ZeroMaxStepsRunsOneStep() {
+        assert_eq!(FixedTimestep::New(10, 0).max_steps, 1);
+
+        let mut fixed_timestep = FixedTimestep {
+            max_steps: 0,
+            ..Default::default()
+        };
+
+        let (steps, alpha) = fixed_timestep.Advance(Duration::from_secs(1));
+        assert_eq!(steps, 1);
+        assert!((0.0..1.0).contains(&alpha));
+    }
+
+    #[test]
+    fn HugeTickRatesStepOneNanosecond() {
+        let mut fixed_timestep = FixedTimestep::New(u32::MAX, 8);
+
+        assert_eq!(fixed_timestep.GetStep(), Duration::from_nanos(1));
+
+        let (steps, alpha) = fixed_timestep.Advance(Duration::from_nanos(3));
+        assert_eq!(steps, 3);
+        assert_eq!(alpha, 0.0);
+
+        let (steps, _) = fixed_timestep.Advance(Duration::from_secs(1));
+        assert_eq!(steps, 8);
+    }
+}
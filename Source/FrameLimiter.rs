@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant};
+
+/// Paces frames to a target rate by sleeping and then spinning until the deadline.
+pub struct FrameLimiter {
+    frame_time: Duration,
+    next_frame: Instant,
+}
+
+impl FrameLimiter {
+    /// Remaining time spent spinning instead of sleeping, covers the OS scheduler granularity.
+    pub const SPIN_THRESHOLD: Duration = Duration::from_millis(2);
+
+    pub fn New(target_fps: u32) -> Self {
+        Self {
+            frame_time: Duration::from_secs_f64(1.0 / target_fps.max(1) as f64),
+            next_frame: Instant::now(),
+        }
+    }
+
+    /// Blocks until the next frame is due.
+    pub fn Wait(&mut self) {
+        let now = Instant::now();
+
+        if self.next_frame > now {
+            let remaining = self.next_frame - now;
+
+            if remaining > Self::SPIN_THRESHOLD {
+                std::thread::sleep(remaining - Self::SPIN_THRESHOLD);
+            }
+
+            while Instant::now() < self.next_frame {
+                std::hint::spin_loop();
+            }
+        }
+
+        // Don't try to catch up on missed frames, start counting from now
+        self.next_frame = (self.next_frame + self.frame_time).max(Instant::now());
+    }
+
+    /// Instant the event loop should wake up at, leaving time to spin until the deadline.
+    pub fn GetWakeTime(&self) -> Instant {
+        self.next_frame
+            .checked_sub(Self::SPIN_THRESHOLD)
+            .unwrap_or(self.next_frame)
+    }
+
+    /// Whether the wake time has passed and the next frame should be requested.
+    pub fn IsDue(&self) -> bool {
+        Instant::now() >= self.GetWakeTime()
+    }
+
+    /// Restarts pacing, used after the loop was paused.
+    pub fn Reset(&mut self) {
+        self.next_frame = Instant::now();
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Frame timing statistics collected by the runtime, available through `Renderer::frame_stats`.
+#[derive(Clone, Debug, Default)]
+pub struct FrameStats {
+    /// Number of frames rendered since start.
+    pub frame_count: u64,
+
+    /// Time between the last two frames.
+    pub last_frame_time: Duration,
+
+    history: VecDeque<Duration>,
+}
+
+impl FrameStats {
+    /// Number of frame times kept for the rolling statistics.
+    pub const HISTORY_LENGTH: usize = 1000;
+
+    pub fn Record(&mut self, frame_time: Duration) {
+        if self.history.len() == Self::HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(frame_time);
+        self.last_frame_time = frame_time;
+        self.frame_count += 1;
+    }
+
+    /// Average frame time over the recorded history.
+    pub fn GetAverageFrameTime(&self) -> Duration {
+        if self.history.is_empty() {
+            return Duration::ZERO;
+        }
+
+        self.history.iter().sum::<Duration>() / self.history.len() as u32
+    }
+
+    /// Average frames per second over the recorded history.
+    pub fn GetFps(&self) -> f32 {
+        Self::ToFps(self.GetAverageFrameTime())
+    }
+
+    /// Frames per second of the slowest 1% of the recorded frames.
+    pub fn GetOnePercentLow(&self) -> f32 {
+        if self.history.is_empty() {
+            return 0.0;
+        }
+
+        let mut frame_times = self.history.iter().copied().collect::<Vec<_>>();
+        frame_times.sort_unstable_by(|a, b| b.cmp(a));
+
+        let count = (frame_times.len() / 100).max(1);
+        let slowest = frame_times[..count].iter().sum::<Duration>() / count as u32;
+
+        Self::ToFps(slowest)
+    }
+
+    fn ToFps(frame_time: Duration) -> f32 {
+        if frame_time.is_zero() {
+            0.0
+        } else {
+            1.0 / frame_time.as_secs_f32()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn EmptyStatsAreZero() {
+        let frame_stats = FrameStats::default();
+
+        assert_eq!(frame_stats.GetAverageFrameTime(), Duration::ZERO);
+        assert_eq!(frame_stats.GetFps(), 0.0);
+        assert_eq!(frame_stats.GetOnePercentLow(), 0.0);
+    }
+
+    #[test]
+    fn AveragesRecordedFrames() {
+        let mut frame_stats = FrameStats::default();
+
+        for millis in [10, 20, 30, 40] {
+            frame_stats.Record(Duration::from_millis(millis));
+        }
+
+        assert_eq!(frame_stats.frame_count, 4);
+        assert_eq!(frame_stats.last_frame_time, Duration::from_millis(40));
+        assert_eq!(frame_stats.GetAverageFrameTime(), Duration::from_millis(25));
+        assert!((frame_stats.GetFps() - 40.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn OnePercentLowUsesSlowestFrames() {
+        let mut frame_stats = FrameStats::default();
+
+        for _ in 0..198 {
+            frame_stats.Record(Duration::from_millis(10));
+        }
+
+        frame_stats.Record(Duration::from_millis(50));
+        frame_stats.Record(Duration::from_millis(100));
+
+        // 1% of 200 frames is the two slowest, averaging 75 ms
+        assert!((frame_stats.GetOnePercentLow() - 1.0 / 0.075).abs() < 1e-3);
+    }
+
+    #[test]
+    fn OnePercentLowUsesAtLeastOneFrame() {
+        let mut frame_stats = FrameStats::default();
+
+        frame_stats.Record(Duration::from_millis(10));
+        frame_stats.Record(Duration::from_millis(20));
+
+        assert!((frame_stats.GetOnePercentLow() - 50.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn HistoryWrapsAfterLimit() {
+        let mut frame_stats = FrameStats::default();
+
+        for _ in 0..FrameStats::HISTORY_LENGTH {
+            frame_stats.Record(Duration::from_millis(100));
+        }
+
+        for _ in 0..FrameStats::HISTORY_LENGTH {
+            frame_stats.Record(Duration::from_millis(10));
+        }
+
+        assert_eq!(
+            frame_stats.frame_count,
+            2 * FrameStats::HISTORY_LENGTH as u64
+        );
+        assert_eq!(frame_stats.history.len(), FrameStats::HISTORY_LENGTH);
+        assert_eq!(frame_stats.GetAverageFrameTime(), Duration::from_millis(10));
+
+        frame_stats.Record(Duration::from_millis(1010));
+        assert_eq!(frame_stats.history.len(), FrameStats::HISTORY_LENGTH);
+        assert_eq!(frame_stats.GetAverageFrameTime(), Duration::from_millis(11));
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// How a sampler handles texture coordinates outside of `[0, 1]`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AddressMode {
+    /// Coordinates are clamped to the edge texels.
+    ClampToEdge = 0,
+
+    /// The texture repeats.
+    Repeat = 1,
+
+    /// The texture repeats, mirrored every other time.
+    MirrorRepeat = 2,
+}
+
+impl Default for AddressMode {
+    fn default() -> Self {
+        Self::ClampToEdge
+    }
+}
+
+impl From<AddressMode> for wgpu::AddressMode {
+    fn from(address_mode: AddressMode) -> Self {
+        match address_mode {
+            AddressMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+            AddressMode::Repeat => wgpu::AddressMode::Repeat,
+            AddressMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
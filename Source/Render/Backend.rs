@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Backend {
+    /// Vulkan API (Windows, Linux, Android)
+    Vulkan = 0,
+
+    /// Metal API (Apple platforms)
+    Metal = 1,
+
+    /// Direct3D 12 (Windows)
+    Dx12 = 2,
+
+    /// Direct3D 11 (Windows)
+    Dx11 = 3,
+
+    /// OpenGL ES 3 (Linux, Android)
+    Gl = 4,
+
+    /// WebGPU in the browser
+    BrowserWebGpu = 5,
+}
+
+impl Backend {
+    /// Parses a backend name as used by the `WGPU_BACKEND` environment variable.
+    pub fn FromName(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "vulkan" | "vk" => Some(Self::Vulkan),
+            "metal" | "mtl" => Some(Self::Metal),
+            "dx12" | "d3d12" => Some(Self::Dx12),
+            "dx11" | "d3d11" => Some(Self::Dx11),
+            "gl" | "gles" | "opengl" => Some(Self::Gl),
+            "webgpu" => Some(Self::BrowserWebGpu),
+            _ => None,
+        }
+    }
+}
+
+impl From<Backend> for wgpu::Backends {
+    fn from(backend: Backend) -> Self {
+        match backend {
+            Backend::Vulkan => wgpu::Backends::VULKAN,
+            Backend::Metal => wgpu::Backends::METAL,
+            Backend::Dx12 => wgpu::Backends::DX12,
+            Backend::Dx11 => wgpu::Backends::DX11,
+            Backend::Gl => wgpu::Backends::GL,
+            Backend::BrowserWebGpu => wgpu::Backends::BROWSER_WEBGPU,
+        }
+    }
+}
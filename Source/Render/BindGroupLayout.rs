@@ -0,0 +1,31 @@
+/// Bind group layout along with the entries it was created from.
+///
+/// wgpu shares layouts with equal entries on a device, so the entries identify the layout
+/// in the [`PipelineCache`](super::PipelineCache).
+pub struct BindGroupLayout {
+    layout: wgpu::BindGroupLayout,
+    entries: Vec<wgpu::BindGroupLayoutEntry>,
+}
+
+impl BindGroupLayout {
+    pub fn New(device: &wgpu::Device, label: &str, entries: &[wgpu::BindGroupLayoutEntry]) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(label),
+            entries,
+        });
+
+        Self {
+            layout,
+            entries: entries.to_vec(),
+        }
+    }
+
+    /// Returns the wgpu layout.
+    pub fn Get(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn GetEntries(&self) -> &[wgpu::BindGroupLayoutEntry] {
+        &self.entries
+    }
+}
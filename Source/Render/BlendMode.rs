@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// Overwrites the target color.
+    Replace = 0,
+
+    /// Blends with the target using straight alpha.
+    AlphaBlending = 1,
+
+    /// Blends with the target using premultiplied alpha.
+    PremultipliedAlphaBlending = 2,
+}
+
+impl Default for BlendMode {
+    fn default() -> Self {
+        Self::Replace
+    }
+}
+
+impl From<BlendMode> for wgpu::BlendState {
+    fn from(blend_mode: BlendMode) -> Self {
+        match blend_mode {
+            BlendMode::Replace => wgpu::BlendState::REPLACE,
+            BlendMode::AlphaBlending => wgpu::BlendState::ALPHA_BLENDING,
+            BlendMode::PremultipliedAlphaBlending => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+        }
+    }
+}
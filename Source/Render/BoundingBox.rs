@@ -0,0 +1,51 @@
+use cgmath::{Point3, Vector3};
+
+/// Axis aligned bounding box.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl BoundingBox {
+    /// Smallest box containing every point, `None` when there are none.
+    pub fn FromPoints(points: impl IntoIterator<Item = Point3<f32>>) -> Option<Self> {
+        points.into_iter().fold(None, |bounds, point| {
+            Some(match bounds {
+                Some(bounds) => bounds.Extend(point),
+                None => Self {
+                    min: point,
+                    max: point,
+                },
+            })
+        })
+    }
+
+    /// Grows the box to contain `point`.
+    pub fn Extend(self, point: Point3<f32>) -> Self {
+        Self {
+            min: Point3::new(
+                self.min.x.min(point.x),
+                self.min.y.min(point.y),
+                self.min.z.min(point.z),
+            ),
+            max: Point3::new(
+                self.max.x.max(point.x),
+                self.max.y.max(point.y),
+                self.max.z.max(point.z),
+            ),
+        }
+    }
+
+    pub fn Union(self, other: Self) -> Self {
+        self.Extend(other.min).Extend(other.max)
+    }
+
+    pub fn GetCenter(&self) -> Point3<f32> {
+        self.min + self.GetSize() / 2.0
+    }
+
+    pub fn GetSize(&self) -> Vector3<f32> {
+        self.max - self.min
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CompareFunction {
+    /// Function never passes
+    Never = 0,
+
+    /// Function passes if new value less than existing value
+    Less = 1,
+
+    /// Function passes if new value is equal to existing value
+    Equal = 2,
+
+    /// Function passes if new value is less than or equal to existing value
+    LessEqual = 3,
+
+    /// Function passes if new value is greater than existing value
+    Greater = 4,
+
+    /// Function passes if new value is not equal to existing value
+    NotEqual = 5,
+
+    /// Function passes if new value is greater than or equal to existing value
+    GreaterEqual = 6,
+
+    /// Function always passes
+    Always = 7,
+}
+
+impl Default for CompareFunction {
+    fn default() -> Self {
+        Self::Less
+    }
+}
+
+impl From<CompareFunction> for wgpu::CompareFunction {
+    fn from(compare: CompareFunction) -> Self {
+        match compare {
+            CompareFunction::Never => wgpu::CompareFunction::Never,
+            CompareFunction::Less => wgpu::CompareFunction::Less,
+            CompareFunction::Equal => wgpu::CompareFunction::Equal,
+            CompareFunction::LessEqual => wgpu::CompareFunction::LessEqual,
+            CompareFunction::Greater => wgpu::CompareFunction::Greater,
+            CompareFunction::NotEqual => wgpu::CompareFunction::NotEqual,
+            CompareFunction::GreaterEqual => wgpu::CompareFunction::GreaterEqual,
+            CompareFunction::Always => wgpu::CompareFunction::Always,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CullMode {
+    /// Both faces are drawn.
+    None = 0,
+
+    /// Front facing polygons are discarded.
+    Front = 1,
+
+    /// Back facing polygons are discarded.
+    Back = 2,
+}
+
+impl Default for CullMode {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl From<CullMode> for Option<wgpu::Face> {
+    fn from(cull_mode: CullMode) -> Self {
+        match cull_mode {
+            CullMode::None => None,
+            CullMode::Front => Some(wgpu::Face::Front),
+            CullMode::Back => Some(wgpu::Face::Back),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use super::{CompareFunction, Texture};
+use serde::{Deserialize, Serialize};
+
+/// Depth testing of a pipeline, the attachment uses [`Texture::DEPTH_FORMAT`].
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DepthStencilState {
+    pub depth_write_enabled: bool,
+    pub depth_compare: CompareFunction,
+
+    /// Constant depth bias added to each fragment.
+    pub depth_bias: i32,
+}
+
+impl Default for DepthStencilState {
+    fn default() -> Self {
+        Self {
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::Less,
+            depth_bias: 0,
+        }
+    }
+}
+
+impl From<DepthStencilState> for wgpu::DepthStencilState {
+    fn from(state: DepthStencilState) -> Self {
+        wgpu::DepthStencilState {
+            format: Texture::DEPTH_FORMAT,
+            depth_write_enabled: state.depth_write_enabled,
+            depth_compare: state.depth_compare.into(),
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState {
+                constant: state.depth_bias,
+                ..Default::default()
+            },
+        }
+    }
+}
@@ -42,7 +42,7 @@ impl<'a> DrawModel<'a> for wgpu::RenderPass<'a> {
         camera_bind_group: &'a wgpu::BindGroup,
     ) {
         self.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
-        self.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        self.set_index_buffer(mesh.index_buffer.slice(..), mesh.index_format.into());
         self.set_bind_group(0, &material.bind_group, &[]);
         self.set_bind_group(1, camera_bind_group, &[]);
         self.draw_indexed(0..mesh.num_elements, 0, instances);
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// How a sampler blends between texels or mip levels.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum FilterMode {
+    /// Takes the closest texel or level.
+    Nearest = 0,
+
+    /// Interpolates between the closest texels or levels.
+    Linear = 1,
+}
+
+impl Default for FilterMode {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+impl From<FilterMode> for wgpu::FilterMode {
+    fn from(filter_mode: FilterMode) -> Self {
+        match filter_mode {
+            FilterMode::Nearest => wgpu::FilterMode::Nearest,
+            FilterMode::Linear => wgpu::FilterMode::Linear,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+/// Color target acquired for a single frame.
+///
+/// Wraps either the swapchain texture of a window surface or a view of the
+/// offscreen render target, so drawing code does not need to care which one.
+pub struct Frame {
+    pub view: wgpu::TextureView,
+    surface_texture: Option<wgpu::SurfaceTexture>,
+}
+
+impl Frame {
+    pub(crate) fn FromSurface(surface_texture: wgpu::SurfaceTexture) -> Self {
+        let view = surface_texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self {
+            view,
+            surface_texture: Some(surface_texture),
+        }
+    }
+
+    pub(crate) fn FromTexture(texture: &wgpu::Texture) -> Self {
+        Self {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            surface_texture: None,
+        }
+    }
+
+    /// Schedules the frame to be presented, no-op for offscreen targets.
+    pub fn Present(self) {
+        if let Some(surface_texture) = self.surface_texture {
+            surface_texture.present();
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum FrontFace {
+    /// Triangles with counter clockwise vertices are front facing.
+    Ccw = 0,
+
+    /// Triangles with clockwise vertices are front facing.
+    Cw = 1,
+}
+
+impl Default for FrontFace {
+    fn default() -> Self {
+        Self::Ccw
+    }
+}
+
+impl From<FrontFace> for wgpu::FrontFace {
+    fn from(front_face: FrontFace) -> Self {
+        match front_face {
+            FrontFace::Ccw => wgpu::FrontFace::Ccw,
+            FrontFace::Cw => wgpu::FrontFace::Cw,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::IndexFormat;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 
@@ -5,10 +6,56 @@ use std::borrow::Cow;
 pub struct IndexBuffer {
     pub label: Cow<'static, str>,
     pub content: Vec<u8>,
+    pub format: IndexFormat,
 }
 
 impl IndexBuffer {
+    pub fn FromU16(label: impl Into<Cow<'static, str>>, indices: &[u16]) -> Self {
+        Self {
+            label: label.into(),
+            content: bytemuck::cast_slice(indices).to_vec(),
+            format: IndexFormat::UInt16,
+        }
+    }
+
+    pub fn FromU32(label: impl Into<Cow<'static, str>>, indices: &[u32]) -> Self {
+        Self {
+            label: label.into(),
+            content: bytemuck::cast_slice(indices).to_vec(),
+            format: IndexFormat::UInt32,
+        }
+    }
+
+    /// Returns the number of indices, not the size in bytes.
     pub fn GetLength(&self) -> u32 {
-        self.content.len() as u32
+        (self.content.len() as u64 / self.format.GetSize()) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn LengthCountsU16Indices() {
+        let indices = IndexBuffer::FromU16("Indices", &[0, 1, 2, 2, 3, 0]);
+
+        assert_eq!(indices.format, IndexFormat::UInt16);
+        assert_eq!(indices.content.len(), 12);
+        assert_eq!(indices.GetLength(), 6);
+    }
+
+    #[test]
+    fn LengthCountsU32Indices() {
+        let indices = IndexBuffer::FromU32("Indices", &[0, 1, 2, 2, 3, 0]);
+
+        assert_eq!(indices.format, IndexFormat::UInt32);
+        assert_eq!(indices.content.len(), 24);
+        assert_eq!(indices.GetLength(), 6);
+    }
+
+    #[test]
+    fn EmptyBufferHasNoIndices() {
+        assert_eq!(IndexBuffer::FromU16("Indices", &[]).GetLength(), 0);
     }
 }
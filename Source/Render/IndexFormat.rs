@@ -10,8 +10,50 @@ pub enum IndexFormat {
     UInt32 = 1,
 }
 
+impl IndexFormat {
+    /// Returns the size in bytes of a single index.
+    pub const fn GetSize(&self) -> u64 {
+        match *self {
+            Self::UInt16 => 2,
+            Self::UInt32 => 4,
+        }
+    }
+}
+
 impl Default for IndexFormat {
     fn default() -> Self {
         Self::UInt32
     }
 }
+
+impl From<IndexFormat> for wgpu::IndexFormat {
+    fn from(format: IndexFormat) -> Self {
+        match format {
+            IndexFormat::UInt16 => wgpu::IndexFormat::Uint16,
+            IndexFormat::UInt32 => wgpu::IndexFormat::Uint32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ConvertsToWgpu() {
+        assert_eq!(
+            wgpu::IndexFormat::from(IndexFormat::UInt16),
+            wgpu::IndexFormat::Uint16
+        );
+        assert_eq!(
+            wgpu::IndexFormat::from(IndexFormat::UInt32),
+            wgpu::IndexFormat::Uint32
+        );
+    }
+
+    #[test]
+    fn SizeIsInBytes() {
+        assert_eq!(IndexFormat::UInt16.GetSize(), 2);
+        assert_eq!(IndexFormat::UInt32.GetSize(), 4);
+    }
+}
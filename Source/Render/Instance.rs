@@ -1,5 +1,5 @@
+use super::Vertex;
 use bytemuck::{Pod, Zeroable};
-use std::mem;
 
 pub const NUM_INSTANCES_PER_ROW: u32 = 10;
 pub const NUM_INSTANCES: u32 = NUM_INSTANCES_PER_ROW * NUM_INSTANCES_PER_ROW;
@@ -25,47 +25,38 @@ impl Instance {
     }
 }
 
+/// A mat4 takes up 4 vertex slots as it is technically 4 vec4s, the matrix is
+/// reassembled in the shader. Locations start at 5 to leave room for the model vertex.
 #[repr(C)]
-#[derive(Copy, Clone, Pod, Zeroable)]
+#[derive(Copy, Clone, Pod, Zeroable, Vertex)]
+#[vertex(step_mode = Instance, location = 5)]
 pub struct InstanceRaw {
     pub model: [[f32; 4]; 4],
 }
 
-impl InstanceRaw {
-    pub fn GetDescriptor<'a>() -> wgpu::VertexBufferLayout<'a> {
-        wgpu::VertexBufferLayout {
-            array_stride: mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
-            // We need to switch from using a step mode of Vertex to Instance
-            // This means that our shaders will only change to use the next
-            // instance when the shader starts processing a new instance
-            step_mode: wgpu::VertexStepMode::Instance,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    offset: 0,
-                    // While our vertex shader only uses locations 0, and 1 now, in later tutorials we'll
-                    // be using 2, 3, and 4, for Vertex. We'll start at slot 5 not conflict with them later
-                    shader_location: 5,
-                    format: wgpu::VertexFormat::Float32x4,
-                },
-                // A mat4 takes up 4 vertex slots as it is technically 4 vec4s. We need to define a slot
-                // for each vec4. We'll have to reassemble the mat4 in
-                // the shader.
-                wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
-                    shader_location: 6,
-                    format: wgpu::VertexFormat::Float32x4,
-                },
-                wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
-                    shader_location: 7,
-                    format: wgpu::VertexFormat::Float32x4,
-                },
-                wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
-                    shader_location: 8,
-                    format: wgpu::VertexFormat::Float32x4,
-                },
-            ],
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Render::{StepMode, VertexFormat};
+
+    #[test]
+    fn DerivedLayoutMatchesMatrixRows() {
+        let layout = InstanceRaw::GetLayout();
+
+        assert_eq!(layout.stride, 64);
+        assert_eq!(layout.step_mode, StepMode::Instance);
+        assert_eq!(
+            layout
+                .attributes
+                .iter()
+                .map(|a| (a.format, a.offset, a.shader_location))
+                .collect::<Vec<_>>(),
+            vec![
+                (VertexFormat::Float32x4, 0, 5),
+                (VertexFormat::Float32x4, 16, 6),
+                (VertexFormat::Float32x4, 32, 7),
+                (VertexFormat::Float32x4, 48, 8),
+            ]
+        );
     }
 }
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum LimitsPreset {
+    /// Limits guaranteed by every WebGPU implementation.
+    Default = 0,
+
+    /// Lower limits supported by older hardware such as GLES3 and D3D11 class devices.
+    Downlevel = 1,
+
+    /// Limits usable on WebGL2.
+    DownlevelWebGl2 = 2,
+
+    /// Everything the selected adapter supports.
+    Adapter = 3,
+}
+
+impl Default for LimitsPreset {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+impl LimitsPreset {
+    pub fn GetLimits(&self, adapter: &wgpu::Adapter) -> wgpu::Limits {
+        match *self {
+            Self::Default => wgpu::Limits::default(),
+            Self::Downlevel => wgpu::Limits::downlevel_defaults(),
+            Self::DownlevelWebGl2 => wgpu::Limits::downlevel_webgl2_defaults(),
+            Self::Adapter => adapter.limits(),
+        }
+    }
+}
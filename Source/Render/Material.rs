@@ -2,6 +2,53 @@ use super::Texture;
 
 pub struct Material {
     pub name: String,
+    /// Base color texture, bound at binding 0 with its sampler at binding 1.
     pub diffuse_texture: Texture,
+    /// Tangent space normal map.
+    pub normal_texture: Option<Texture>,
+    /// Linear RGBA multiplied with the diffuse texture.
+    pub base_color_factor: [f32; 4],
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    /// Linear RGB emitted color.
+    pub emissive_factor: [f32; 3],
     pub bind_group: wgpu::BindGroup,
 }
+
+impl Material {
+    /// Creates a material with neutral PBR factors, binding the diffuse texture with `layout`.
+    pub fn New(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        name: impl Into<String>,
+        diffuse_texture: Texture,
+    ) -> Self {
+        let name = name.into();
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
+                },
+            ],
+            label: Some(&name),
+        });
+
+        Self {
+            name,
+            diffuse_texture,
+            normal_texture: None,
+            base_color_factor: [1.0; 4],
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            emissive_factor: [0.0; 3],
+            bind_group,
+        }
+    }
+}
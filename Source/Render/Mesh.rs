@@ -1,7 +1,13 @@
+use super::{IndexFormat, MeshData};
+
 pub struct Mesh {
     pub name: String,
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
+    pub index_format: IndexFormat,
     pub num_elements: u32,
     pub material: usize,
+    /// Geometry the buffers were created from, kept when loading with
+    /// [`ModelOptions::keep_mesh_data`](super::ModelOptions::keep_mesh_data).
+    pub data: Option<MeshData>,
 }
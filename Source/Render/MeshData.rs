@@ -0,0 +1,617 @@
+use super::{BoundingBox, IndexFormat, Mesh, ModelVertex, NormalMode};
+use cgmath::{InnerSpace, Matrix, Matrix3, Matrix4, Point3, SquareMatrix, Transform, Vector3};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wgpu::util::DeviceExt;
+
+/// Triangle list geometry kept on the CPU, uploaded with [`MeshData::Upload`].
+///
+/// Optional attributes are either empty or hold one value per position.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeshData {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    /// First UV set, the one uploaded with the vertices.
+    pub tex_coords: Vec<[f32; 2]>,
+    /// Second UV set, only kept on the CPU.
+    pub tex_coords_1: Vec<[f32; 2]>,
+    /// Tangent direction, `w` holds the handedness of the bitangent.
+    pub tangents: Vec<[f32; 4]>,
+    /// Linear RGBA vertex colors.
+    pub colors: Vec<[f32; 4]>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    pub fn New() -> Self {
+        Self::default()
+    }
+
+    pub fn FromVertices(vertices: &[ModelVertex], indices: Vec<u32>) -> Self {
+        Self {
+            positions: vertices.iter().map(|v| v.position).collect(),
+            normals: vertices.iter().map(|v| v.normal).collect(),
+            tex_coords: vertices.iter().map(|v| v.tex_coords).collect(),
+            tex_coords_1: Vec::new(),
+            tangents: vertices.iter().map(|v| v.tangent).collect(),
+            colors: Vec::new(),
+            indices,
+        }
+    }
+
+    pub fn GetVertexCount(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn GetTriangleCount(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Interleaves the attributes, missing ones are filled with defaults.
+    pub fn GetVertices(&self) -> Vec<ModelVertex> {
+        (0..self.GetVertexCount())
+            .map(|i| ModelVertex {
+                position: self.positions[i],
+                tex_coords: self.tex_coords.get(i).copied().unwrap_or_default(),
+                normal: self.normals.get(i).copied().unwrap_or_default(),
+                tangent: self
+                    .tangents
+                    .get(i)
+                    .copied()
+                    .unwrap_or(ModelVertex::DEFAULT_TANGENT),
+            })
+            .collect()
+    }
+
+    /// Replaces the normals with ones computed from the triangles.
+    ///
+    /// Flat normals give every triangle its own vertices.
+    pub fn ComputeNormals(&mut self, mode: NormalMode) {
+        if mode == NormalMode::Flat {
+            self.Unweld();
+        }
+
+        let mut normals = vec![Vector3::new(0.0, 0.0, 0.0); self.GetVertexCount()];
+
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| Vector3::from(self.positions[triangle[i] as usize]));
+
+            // The cross product length is twice the area, larger faces weigh more
+            let normal = (b - a).cross(c - a);
+
+            for &i in triangle {
+                normals[i as usize] += normal;
+            }
+        }
+
+        self.normals = normals.into_iter().map(|n| Normalize(n).into()).collect();
+    }
+
+    /// Replaces the tangents with MikkTSpace tangents, the convention normal maps are baked with.
+    ///
+    /// Vertices shared by faces with different tangents, at UV seams or mirrored UVs, are
+    /// split. Requires normals and texture coordinates, returns whether tangents were generated.
+    pub fn ComputeTangents(&mut self) -> bool {
+        let count = self.GetVertexCount();
+
+        if self.normals.len() != count || self.tex_coords.len() != count {
+            return false;
+        }
+
+        // One tangent per face corner, corners that agree are welded back afterwards
+        let mut data = self.clone();
+        data.Unweld();
+
+        let mut geometry = TangentGeometry {
+            mesh: &data,
+            tangents: vec![ModelVertex::DEFAULT_TANGENT; data.GetVertexCount()],
+        };
+
+        if !mikktspace::generate_tangents(&mut geometry) {
+            return false;
+        }
+
+        data.tangents = geometry.tangents;
+        data.Weld(0.0);
+
+        *self = data;
+        true
+    }
+
+    /// Merges vertices whose attributes all match within `epsilon`, zero compares exactly.
+    pub fn Weld(&mut self, epsilon: f32) {
+        let quantize = |value: f32| {
+            if epsilon > 0.0 {
+                (value / epsilon).round() as i64
+            } else {
+                // Adding zero turns -0.0 into 0.0
+                (value + 0.0).to_bits() as i64
+            }
+        };
+
+        let mut unique = HashMap::<Vec<i64>, u32>::new();
+        let mut remap = Vec::with_capacity(self.GetVertexCount());
+        let mut kept = Vec::new();
+
+        for i in 0..self.GetVertexCount() {
+            let key = self.positions[i]
+                .iter()
+                .chain(self.normals.get(i).into_iter().flatten())
+                .chain(self.tex_coords.get(i).into_iter().flatten())
+                .chain(self.tex_coords_1.get(i).into_iter().flatten())
+                .chain(self.tangents.get(i).into_iter().flatten())
+                .chain(self.colors.get(i).into_iter().flatten())
+                .map(|&value| quantize(value))
+                .collect::<Vec<_>>();
+
+            let index = *unique.entry(key).or_insert_with(|| {
+                kept.push(i);
+                kept.len() as u32 - 1
+            });
+
+            remap.push(index);
+        }
+
+        for index in &mut self.indices {
+            *index = remap[*index as usize];
+        }
+
+        Select(&mut self.positions, &kept);
+        Select(&mut self.normals, &kept);
+        Select(&mut self.tex_coords, &kept);
+        Select(&mut self.tex_coords_1, &kept);
+        Select(&mut self.tangents, &kept);
+        Select(&mut self.colors, &kept);
+    }
+
+    /// Appends the geometry of `other`, attributes missing on one side are filled with defaults.
+    pub fn Merge(&mut self, other: &MeshData) {
+        let count = self.GetVertexCount();
+        let other_count = other.GetVertexCount();
+
+        Append(
+            &mut self.normals,
+            count,
+            &other.normals,
+            other_count,
+            [0.0; 3],
+        );
+        Append(
+            &mut self.tex_coords,
+            count,
+            &other.tex_coords,
+            other_count,
+            [0.0; 2],
+        );
+        Append(
+            &mut self.tex_coords_1,
+            count,
+            &other.tex_coords_1,
+            other_count,
+            [0.0; 2],
+        );
+        Append(
+            &mut self.tangents,
+            count,
+            &other.tangents,
+            other_count,
+            ModelVertex::DEFAULT_TANGENT,
+        );
+        Append(
+            &mut self.colors,
+            count,
+            &other.colors,
+            other_count,
+            [1.0; 4],
+        );
+
+        self.positions.extend_from_slice(&other.positions);
+        self.indices
+            .extend(other.indices.iter().map(|&i| i + count as u32));
+    }
+
+    /// Transforms positions, normals and tangents, mirroring transforms also flip the winding.
+    pub fn Transform(&mut self, matrix: Matrix4<f32>) {
+        let linear = Matrix3::from_cols(
+            matrix.x.truncate(),
+            matrix.y.truncate(),
+            matrix.z.truncate(),
+        );
+        let normal_matrix = linear.invert().map_or(linear, |m| m.transpose());
+        let is_mirrored = linear.determinant() < 0.0;
+
+        for position in &mut self.positions {
+            *position = matrix.transform_point(Point3::from(*position)).into();
+        }
+
+        for normal in &mut self.normals {
+            *normal = Normalize(normal_matrix * Vector3::from(*normal)).into();
+        }
+
+        for tangent in &mut self.tangents {
+            let [x, y, z, w] = *tangent;
+            let direction = Normalize(linear * Vector3::new(x, y, z));
+            let w = if is_mirrored { -w } else { w };
+
+            *tangent = [direction.x, direction.y, direction.z, w];
+        }
+
+        if is_mirrored {
+            for triangle in self.indices.chunks_exact_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+    }
+
+    /// Bounds of the positions, `None` when the mesh is empty.
+    pub fn GetBounds(&self) -> Option<BoundingBox> {
+        BoundingBox::FromPoints(self.positions.iter().copied().map(Point3::from))
+    }
+
+    /// Smallest index format able to address every vertex.
+    pub fn GetIndexFormat(&self) -> IndexFormat {
+        if self.GetVertexCount() <= u16::MAX as usize + 1 {
+            IndexFormat::UInt16
+        } else {
+            IndexFormat::UInt32
+        }
+    }
+
+    /// Creates the GPU buffers, indices use the format from [`MeshData::GetIndexFormat`].
+    pub fn Upload(&self, device: &wgpu::Device, name: impl Into<String>, material: usize) -> Mesh {
+        let name = name.into();
+        let index_format = self.GetIndexFormat();
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Vertex Buffer", name)),
+            contents: bytemuck::cast_slice(&self.GetVertices()),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let indices = match index_format {
+            IndexFormat::UInt16 => {
+                let indices = self.indices.iter().map(|&i| i as u16).collect::<Vec<_>>();
+                bytemuck::cast_slice(&indices).to_vec()
+            }
+            IndexFormat::UInt32 => bytemuck::cast_slice(&self.indices).to_vec(),
+        };
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Index Buffer", name)),
+            contents: &indices,
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Mesh {
+            name,
+            vertex_buffer,
+            index_buffer,
+            index_format,
+            num_elements: self.indices.len() as u32,
+            material,
+            data: None,
+        }
+    }
+
+    /// Gives every index its own vertex.
+    fn Unweld(&mut self) {
+        let indices = std::mem::take(&mut self.indices);
+
+        Expand(&mut self.positions, &indices);
+        Expand(&mut self.normals, &indices);
+        Expand(&mut self.tex_coords, &indices);
+        Expand(&mut self.tex_coords_1, &indices);
+        Expand(&mut self.tangents, &indices);
+        Expand(&mut self.colors, &indices);
+
+        self.indices = (0..indices.len() as u32).collect();
+    }
+}
+
+struct TangentGeometry<'a> {
+    mesh: &'a MeshData,
+    tangents: Vec<[f32; 4]>,
+}
+
+impl TangentGeometry<'_> {
+    fn GetIndex(&self, face: usize, vert: usize) -> usize {
+        self.mesh.indices[face * 3 + vert] as usize
+    }
+}
+
+impl mikktspace::Geometry for TangentGeometry<'_> {
+    fn num_faces(&self) -> usize {
+        self.mesh.GetTriangleCount()
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.positions[self.GetIndex(face, vert)]
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.mesh.normals[self.GetIndex(face, vert)]
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.mesh.tex_coords[self.GetIndex(face, vert)]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        let index = self.GetIndex(face, vert);
+        self.tangents[index] = tangent;
+    }
+}
+
+fn Normalize(v: Vector3<f32>) -> Vector3<f32> {
+    if v.magnitude2() > 0.0 {
+        v.normalize()
+    } else {
+        v
+    }
+}
+
+fn Expand<T: Copy>(values: &mut Vec<T>, indices: &[u32]) {
+    if !values.is_empty() {
+        *values = indices.iter().map(|&i| values[i as usize]).collect();
+    }
+}
+
+fn Select<T: Copy>(values: &mut Vec<T>, kept: &[usize]) {
+    if !values.is_empty() {
+        *values = kept.iter().map(|&i| values[i]).collect();
+    }
+}
+
+fn Append<T: Copy>(values: &mut Vec<T>, count: usize, other: &[T], other_count: usize, default: T) {
+    if values.is_empty() && other.is_empty() {
+        return;
+    }
+
+    values.resize(count, default);
+
+    if other.is_empty() {
+        values.resize(count + other_count, default);
+    } else {
+        values.extend_from_slice(other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two triangles sharing the edge from 0 to 2, the second with its UVs mirrored.
+    fn MirroredUvs() -> MeshData {
+        MeshData {
+            positions: vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [-1.0, 0.0, 0.0],
+            ],
+            normals: vec![[0.0, 0.0, 1.0]; 4],
+            tex_coords: vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 0.0]],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            ..Default::default()
+        }
+    }
+
+    fn GetFaceNormal(data: &MeshData, face: usize) -> [f32; 3] {
+        let [a, b, c] =
+            [0, 1, 2].map(|i| Vector3::from(data.positions[data.indices[face * 3 + i] as usize]));
+
+        Normalize((b - a).cross(c - a)).into()
+    }
+
+    fn AssertNear(a: [f32; 4], b: [f32; 4]) {
+        assert!(
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn TangentsAreSplitAtMirroredUvs() {
+        let mut data = MirroredUvs();
+
+        assert!(data.ComputeTangents());
+
+        // The shared corners differ in tangent, each face keeps its own
+        assert_eq!(data.GetVertexCount(), 6);
+        assert_eq!(data.GetTriangleCount(), 2);
+
+        for &i in &data.indices[..3] {
+            AssertNear(data.tangents[i as usize], [1.0, 0.0, 0.0, 1.0]);
+        }
+
+        for &i in &data.indices[3..] {
+            AssertNear(data.tangents[i as usize], [-1.0, 0.0, 0.0, -1.0]);
+        }
+    }
+
+    #[test]
+    fn MatchingTangentsStayWelded() {
+        let mut data = MeshData {
+            positions: vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+            ],
+            normals: vec![[0.0, 0.0, 1.0]; 4],
+            tex_coords: vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            ..Default::default()
+        };
+
+        assert!(data.ComputeTangents());
+        assert_eq!(data.GetVertexCount(), 4);
+
+        for tangent in &data.tangents {
+            AssertNear(*tangent, [1.0, 0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn TangentsRequireNormalsAndTexCoords() {
+        let mut data = MirroredUvs();
+        data.tex_coords.clear();
+
+        assert!(!data.ComputeTangents());
+        assert!(data.tangents.is_empty());
+        assert_eq!(data.GetVertexCount(), 4);
+    }
+
+    #[test]
+    fn WeldUsesEpsilon() {
+        let data = MeshData {
+            positions: vec![[0.0, 0.0, 0.0], [0.0001, 0.0, 0.0], [1.0, 0.0, 0.0]],
+            indices: vec![0, 1, 2],
+            ..Default::default()
+        };
+
+        let mut exact = data.clone();
+        exact.Weld(0.0);
+        assert_eq!(exact.GetVertexCount(), 3);
+
+        let mut loose = data;
+        loose.Weld(0.001);
+        assert_eq!(loose.GetVertexCount(), 2);
+        assert_eq!(loose.indices, [0, 0, 1]);
+    }
+
+    #[test]
+    fn WeldTreatsNegativeZeroAsZero() {
+        let mut data = MeshData {
+            positions: vec![[0.0, 1.0, 0.0], [-0.0, 1.0, -0.0]],
+            normals: vec![[0.0, 0.0, 1.0], [-0.0, 0.0, 1.0]],
+            indices: vec![0, 1, 1],
+            ..Default::default()
+        };
+
+        data.Weld(0.0);
+
+        assert_eq!(data.GetVertexCount(), 1);
+        assert_eq!(data.indices, [0, 0, 0]);
+    }
+
+    #[test]
+    fn WeldKeepsDifferentAttributes() {
+        let mut data = MeshData {
+            positions: vec![[0.0; 3]; 2],
+            tex_coords: vec![[0.0, 0.0], [1.0, 0.0]],
+            indices: vec![0, 1, 0],
+            ..Default::default()
+        };
+
+        data.Weld(0.0);
+
+        assert_eq!(data.GetVertexCount(), 2);
+    }
+
+    #[test]
+    fn MergeFillsMissingAttributes() {
+        let mut data = MeshData {
+            positions: vec![[0.0; 3]; 3],
+            normals: vec![[0.0, 0.0, 1.0]; 3],
+            indices: vec![0, 1, 2],
+            ..Default::default()
+        };
+        let other = MeshData {
+            positions: vec![[1.0; 3]; 3],
+            colors: vec![[0.5; 4]; 3],
+            indices: vec![0, 2, 1],
+            ..Default::default()
+        };
+
+        data.Merge(&other);
+
+        assert_eq!(data.GetVertexCount(), 6);
+        assert_eq!(data.indices, [0, 1, 2, 3, 5, 4]);
+        assert_eq!(data.normals[..3], [[0.0, 0.0, 1.0]; 3]);
+        assert_eq!(data.normals[3..], [[0.0; 3]; 3]);
+        assert_eq!(data.colors[..3], [[1.0; 4]; 3]);
+        assert_eq!(data.colors[3..], [[0.5; 4]; 3]);
+        assert!(data.tex_coords.is_empty());
+        assert!(data.tangents.is_empty());
+    }
+
+    #[test]
+    fn MirroringTransformFlipsWindingAndHandedness() {
+        let mut data = MirroredUvs();
+        data.tangents = vec![[1.0, 0.0, 0.0, 1.0]; 4];
+
+        let normal_before = GetFaceNormal(&data, 0);
+        data.Transform(Matrix4::from_nonuniform_scale(-1.0, 1.0, 1.0));
+
+        assert_eq!(data.indices, [0, 2, 1, 0, 3, 2]);
+        assert_eq!(data.positions[1], [-1.0, 0.0, 0.0]);
+        assert_eq!(data.tangents[0], [-1.0, 0.0, 0.0, -1.0]);
+
+        // The faces still point along their normals
+        assert_eq!(data.normals[0], [0.0, 0.0, 1.0]);
+        assert_eq!(GetFaceNormal(&data, 0), normal_before);
+    }
+
+    #[test]
+    fn RotationKeepsWinding() {
+        let mut data = MirroredUvs();
+
+        data.Transform(Matrix4::from_angle_z(cgmath::Deg(90.0)));
+
+        assert_eq!(data.indices, [0, 1, 2, 0, 2, 3]);
+        assert!(data.normals[0][2] > 0.99);
+    }
+
+    #[test]
+    fn FlatAndSmoothNormals() {
+        // Two faces folded along the shared edge from 0 to 1
+        let data = MeshData {
+            positions: vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, 0.0, -1.0],
+            ],
+            indices: vec![0, 1, 2, 0, 1, 3],
+            ..Default::default()
+        };
+
+        let mut smooth = data.clone();
+        smooth.ComputeNormals(NormalMode::Smooth);
+
+        let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+        assert_eq!(smooth.GetVertexCount(), 4);
+        assert!((smooth.normals[0][1] - diagonal).abs() < 1e-6);
+        assert!((smooth.normals[0][2] - diagonal).abs() < 1e-6);
+        assert_eq!(smooth.normals[2], [0.0, 0.0, 1.0]);
+        assert_eq!(smooth.normals[3], [0.0, 1.0, 0.0]);
+
+        let mut flat = data;
+        flat.ComputeNormals(NormalMode::Flat);
+
+        assert_eq!(flat.GetVertexCount(), 6);
+        assert_eq!(flat.normals[..3], [[0.0, 0.0, 1.0]; 3]);
+        assert_eq!(flat.normals[3..], [[0.0, 1.0, 0.0]; 3]);
+    }
+
+    #[test]
+    fn IndexFormatDependsOnVertexCount() {
+        let mut data = MeshData {
+            positions: vec![[0.0; 3]; u16::MAX as usize + 1],
+            ..Default::default()
+        };
+
+        assert_eq!(data.GetIndexFormat(), IndexFormat::UInt16);
+
+        data.positions.push([0.0; 3]);
+        assert_eq!(data.GetIndexFormat(), IndexFormat::UInt32);
+    }
+}
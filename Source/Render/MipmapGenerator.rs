@@ -0,0 +1,262 @@
+use crate::Color::RgbaColor;
+use crate::Shader::Shader;
+use image::imageops::FilterType;
+use image::{ImageBuffer, Rgba, RgbaImage};
+
+const SHADER: &str = r#"
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // Single triangle covering the whole target
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+}
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let last = textureDimensions(source) - vec2<i32>(1, 1);
+    let origin = vec2<i32>(position.xy) * 2;
+
+    // Weight colors by alpha so transparent texels don't bleed into their neighbours
+    var color = vec3<f32>(0.0, 0.0, 0.0);
+    var alpha = 0.0;
+    for (var i = 0; i < 4; i = i + 1) {
+        let texel = textureLoad(source, min(origin + vec2<i32>(i % 2, i / 2), last), 0);
+        color = color + texel.rgb * texel.a;
+        alpha = alpha + texel.a;
+    }
+
+    if (alpha > 0.0) {
+        color = color / alpha;
+    }
+
+    return vec4<f32>(color, alpha / 4.0);
+}
+"#;
+
+/// Fills the mip chain of a texture from its first level.
+///
+/// Each level averages 2x2 texels of the previous one, weighting colors by alpha.
+/// sRGB formats are decoded on load and encoded again on write, so the average is
+/// taken in linear space.
+pub struct MipmapGenerator {
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl MipmapGenerator {
+    pub fn New(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Mipmap Shader"),
+            source: wgpu::ShaderSource::SpirV(
+                Shader::FromWgsl(SHADER)
+                    .CompileSpirv()
+                    .expect("Mipmap shader is valid")
+                    .into(),
+            ),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mipmap Pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[format.into()],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self { pipeline }
+    }
+
+    /// Renders levels `1..mip_level_count` of `texture`.
+    ///
+    /// The texture needs the `TEXTURE_BINDING` and `RENDER_ATTACHMENT` usages and the
+    /// format the generator was created with.
+    pub fn Generate(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+        mip_level_count: u32,
+    ) {
+        let views = (0..mip_level_count)
+            .map(|level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Mipmap View"),
+                    base_mip_level: level,
+                    mip_level_count: std::num::NonZeroU32::new(1),
+                    ..Default::default()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let bind_group_layout = self.pipeline.get_bind_group_layout(0);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Mipmap Encoder"),
+        });
+
+        for pair in views.windows(2) {
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mipmap Bind Group"),
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&pair[0]),
+                }],
+            });
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mipmap Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &pair[1],
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Downsamples `image` on the CPU, returning levels `1..mip_level_count`.
+    ///
+    /// Colors are weighted by alpha and, for sRGB images, filtered in linear space.
+    /// Alpha is always linear.
+    pub fn GenerateLevels(image: &RgbaImage, srgb: bool, mip_level_count: u32) -> Vec<RgbaImage> {
+        let decode = |value: u8| {
+            let value = value as f32 / 255.0;
+
+            if srgb {
+                RgbaColor::SrgbToLinear(value)
+            } else {
+                value
+            }
+        };
+
+        let encode = |value: f32| {
+            let value = value.clamp(0.0, 1.0);
+            let value = if srgb {
+                RgbaColor::LinearToSrgb(value)
+            } else {
+                value
+            };
+            (value * 255.0).round() as u8
+        };
+
+        // Premultiplied, so transparent texels don't bleed their color into the average
+        let mut current =
+            ImageBuffer::<Rgba<f32>, Vec<f32>>::from_fn(image.width(), image.height(), |x, y| {
+                let [r, g, b, a] = image.get_pixel(x, y).0;
+                let a = a as f32 / 255.0;
+                Rgba([decode(r) * a, decode(g) * a, decode(b) * a, a])
+            });
+
+        let mut levels = Vec::new();
+
+        for level in 1..mip_level_count {
+            let width = (image.width() >> level).max(1);
+            let height = (image.height() >> level).max(1);
+
+            // Each level halves the previous one, the triangle filter then averages 2x2 texels
+            current = image::imageops::resize(&current, width, height, FilterType::Triangle);
+
+            levels.push(RgbaImage::from_fn(width, height, |x, y| {
+                let [r, g, b, a] = current.get_pixel(x, y).0;
+                let unpremultiply = if a > 0.0 { 1.0 / a } else { 0.0 };
+
+                Rgba([
+                    encode(r * unpremultiply),
+                    encode(g * unpremultiply),
+                    encode(b * unpremultiply),
+                    (a.clamp(0.0, 1.0) * 255.0).round() as u8,
+                ])
+            }));
+        }
+
+        levels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ShaderIsValid() {
+        Shader::FromWgsl(SHADER).Validate().unwrap();
+    }
+
+    #[test]
+    fn AveragesSrgbInLinearSpace() {
+        let image = RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            }
+        });
+
+        let levels = MipmapGenerator::GenerateLevels(&image, true, 2);
+
+        assert_eq!(levels.len(), 1);
+        assert_eq!(levels[0].dimensions(), (1, 1));
+
+        // Half of linear white is 188 once encoded, not the 128 of a naive average
+        let [r, g, b, a] = levels[0].get_pixel(0, 0).0;
+        for value in [r, g, b] {
+            assert!((187..=189).contains(&value), "{}", value);
+        }
+        assert_eq!(a, 255);
+
+        let levels = MipmapGenerator::GenerateLevels(&image, false, 2);
+        let [r, ..] = levels[0].get_pixel(0, 0).0;
+        assert!((127..=128).contains(&r), "{}", r);
+    }
+
+    #[test]
+    fn TransparentTexelsDontBleed() {
+        let image = RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 255, 0, 0])
+            }
+        });
+
+        let levels = MipmapGenerator::GenerateLevels(&image, true, 2);
+        let [r, g, b, a] = levels[0].get_pixel(0, 0).0;
+
+        assert_eq!([r, g, b], [255, 0, 0]);
+        assert!((127..=128).contains(&a), "{}", a);
+    }
+
+    #[test]
+    fn FullyTransparentLevelsStayBlack() {
+        let image = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 0]));
+
+        let levels = MipmapGenerator::GenerateLevels(&image, true, 3);
+
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[1].get_pixel(0, 0).0, [0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,56 @@
+use super::MipmapGenerator;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Mipmap generators of a single device, one per texture format.
+///
+/// Kept by the owner of the device, see [`Renderer::mipmap_generators`](super::Renderer::mipmap_generators),
+/// and passed to the texture loaders so generators are dropped along with the device.
+#[derive(Default)]
+pub struct MipmapGeneratorCache {
+    generators: Mutex<HashMap<wgpu::TextureFormat, Arc<MipmapGenerator>>>,
+}
+
+impl MipmapGeneratorCache {
+    pub fn New() -> Self {
+        Self::default()
+    }
+
+    /// Returns the generator for `format`, creating it on `device` on first use.
+    pub fn Get(&self, device: &wgpu::Device, format: wgpu::TextureFormat) -> Arc<MipmapGenerator> {
+        self.generators
+            .lock()
+            .unwrap()
+            .entry(format)
+            .or_insert_with(|| Arc::new(MipmapGenerator::New(device, format)))
+            .clone()
+    }
+
+    /// Drops every generator, call when the device they were built on is replaced.
+    pub fn Clear(&self) {
+        self.generators.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Render::Renderer;
+
+    #[test]
+    #[ignore = "needs an adapter"]
+    fn ReusesGeneratorsPerFormat() {
+        let renderer = Renderer::NewForTests();
+        let cache = MipmapGeneratorCache::New();
+
+        let srgb = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let linear = wgpu::TextureFormat::Rgba8Unorm;
+
+        let first = cache.Get(&renderer.device, srgb);
+        assert!(Arc::ptr_eq(&first, &cache.Get(&renderer.device, srgb)));
+        assert!(!Arc::ptr_eq(&first, &cache.Get(&renderer.device, linear)));
+
+        cache.Clear();
+        assert!(!Arc::ptr_eq(&first, &cache.Get(&renderer.device, srgb)));
+    }
+}
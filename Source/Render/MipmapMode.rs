@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// How the mip chain of an uploaded texture is generated.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MipmapMode {
+    /// Only the full resolution level is uploaded.
+    Disabled = 0,
+
+    /// Levels are downsampled on the GPU with linear filtering.
+    Gpu = 1,
+
+    /// Levels are downsampled on the CPU and uploaded with the image, slower but
+    /// doesn't require the format to be renderable.
+    Cpu = 2,
+}
+
+impl Default for MipmapMode {
+    fn default() -> Self {
+        Self::Gpu
+    }
+}
@@ -1,139 +1,1038 @@
-use super::Texture;
-use super::{Material, Mesh, Vertex};
-use anyhow::Result;
+use super::{
+    AddressMode, FilterMode, Material, Mesh, MeshData, MipmapGeneratorCache, MipmapMode, ModelNode,
+    ModelOptions, NormalMode, RendererError, Texture, TextureOptions, Vertex,
+};
 use bytemuck::{Pod, Zeroable};
-use std::path::Path;
+use cgmath::{Matrix4, SquareMatrix};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use tobj::LoadOptions;
-use wgpu::util::DeviceExt;
 
 pub struct Model {
     pub meshes: Vec<Mesh>,
     pub materials: Vec<Material>,
+    /// Scene hierarchy, empty for formats without one such as OBJ.
+    pub nodes: Vec<ModelNode>,
 }
 
 impl Model {
+    /// Loads an OBJ or glTF 2.0 model, the format is picked from the extension.
+    ///
+    /// Material textures are bound with `layout`, which expects the diffuse texture at
+    /// binding 0 and its sampler at binding 1.
     pub fn Load<P: AsRef<Path>>(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
+        generators: &MipmapGeneratorCache,
         layout: &wgpu::BindGroupLayout,
         path: P,
-    ) -> Result<Self> {
+    ) -> Result<Self, RendererError> {
+        Self::LoadWith(
+            device,
+            queue,
+            generators,
+            layout,
+            path,
+            &ModelOptions::default(),
+        )
+    }
+
+    pub fn LoadWith<P: AsRef<Path>>(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        generators: &MipmapGeneratorCache,
+        layout: &wgpu::BindGroupLayout,
+        path: P,
+        options: &ModelOptions,
+    ) -> Result<Self, RendererError> {
+        let path = path.as_ref();
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+
+        match extension.as_deref() {
+            Some("gltf" | "glb") => {
+                Self::LoadGltf(device, queue, generators, layout, path, options)
+            }
+            _ => Self::LoadObj(device, queue, generators, layout, path, options),
+        }
+    }
+
+    /// Wraps geometry such as a [`Primitive`](super::Primitive) in a single mesh model with
+    /// a plain white material, ready for [`DrawModel`](super::DrawModel).
+    pub fn FromMeshData(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        name: impl Into<String>,
+        data: MeshData,
+        options: &ModelOptions,
+    ) -> Result<Self, RendererError> {
+        let material = Self::CreateDefaultMaterial(device, queue, layout)?;
+        let mesh = Self::CreateMesh(device, name.into(), data, 0, options);
+
+        Ok(Self {
+            meshes: vec![mesh],
+            materials: vec![material],
+            nodes: Vec::new(),
+        })
+    }
+
+    /// Loads an OBJ model with its MTL materials.
+    ///
+    /// Missing normals are generated, missing texture coordinates default to zero and
+    /// missing materials or textures fall back to plain white, each reported as a warning.
+    pub fn LoadObj<P: AsRef<Path>>(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        generators: &MipmapGeneratorCache,
+        layout: &wgpu::BindGroupLayout,
+        path: P,
+        options: &ModelOptions,
+    ) -> Result<Self, RendererError> {
+        let path = path.as_ref();
+
+        let (obj_meshes, obj_materials) = Self::LoadObjMeshes(path, options)?;
+
+        let load_texture = |name: &str, file: &str, options: &TextureOptions| {
+            Texture::LoadWith(
+                device,
+                queue,
+                generators,
+                Self::GetObjAssetPath(path, file),
+                options,
+            )
+            .map_err(|e| log::warn!("{:?}: material {:?}: {}", path, name, e))
+            .ok()
+        };
+
+        let mut materials = Vec::new();
+        for mat in obj_materials {
+            let diffuse_texture = match mat.diffuse_texture.as_str() {
+                "" => None,
+                file => load_texture(&mat.name, file, &TextureOptions::default()),
+            };
+
+            let diffuse_texture = match diffuse_texture {
+                Some(texture) => texture,
+                None => Texture::FromColor(device, queue, [255; 4], &mat.name)?,
+            };
+
+            let normal_texture = match mat.normal_texture.as_str() {
+                "" => None,
+                file => load_texture(&mat.name, file, &TextureOptions::Linear()),
+            };
+
+            let mut material = Material::New(device, layout, mat.name, diffuse_texture);
+            material.normal_texture = normal_texture;
+            materials.push(material);
+        }
+
+        // Meshes without a valid material use a plain white one, appended when needed
+        let default_material = materials.len();
+
+        let meshes = obj_meshes
+            .into_iter()
+            .map(|mesh| {
+                let material = mesh.material.unwrap_or(default_material);
+                Self::CreateMesh(device, mesh.name, mesh.data, material, options)
+            })
+            .collect::<Vec<_>>();
+
+        if meshes.iter().any(|mesh| mesh.material == default_material) {
+            materials.push(Self::CreateDefaultMaterial(device, queue, layout)?);
+        }
+
+        Ok(Self {
+            meshes,
+            materials,
+            nodes: Vec::new(),
+        })
+    }
+
+    /// Resolves a file referenced by the MTL file of `path`, such as a texture.
+    ///
+    /// We're assuming that the files are stored with the obj file, a bare file name
+    /// resolves next to it in the working directory.
+    fn GetObjAssetPath(path: &Path, file: &str) -> PathBuf {
+        path.parent().unwrap_or_else(|| Path::new("")).join(file)
+    }
+
+    /// Reads the meshes and MTL materials of an OBJ file, generating missing normals.
+    ///
+    /// Meshes without a material or with one missing from the MTL file have none.
+    fn LoadObjMeshes(
+        path: &Path,
+        options: &ModelOptions,
+    ) -> Result<(Vec<LoadedMesh>, Vec<tobj::Material>), RendererError> {
         let (obj_models, obj_materials) = tobj::load_obj(
-            path.as_ref(),
+            path,
             &LoadOptions {
                 triangulate: true,
                 single_index: true,
                 ..Default::default()
             },
-        )?;
+        )
+        .map_err(|e| RendererError::AssetLoad {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+
+        let obj_materials = obj_materials.unwrap_or_else(|e| {
+            log::warn!("{:?}: failed to load materials: {}", path, e);
+            Vec::new()
+        });
 
-        let obj_materials = obj_materials?;
+        let mut meshes = Vec::new();
+        for m in obj_models {
+            let mut data = MeshData {
+                positions: m
+                    .mesh
+                    .positions
+                    .chunks_exact(3)
+                    .map(|p| [p[0], p[1], p[2]])
+                    .collect(),
+                normals: m
+                    .mesh
+                    .normals
+                    .chunks_exact(3)
+                    .map(|n| [n[0], n[1], n[2]])
+                    .collect(),
+                tex_coords: m
+                    .mesh
+                    .texcoords
+                    .chunks_exact(2)
+                    .map(|t| [t[0], t[1]])
+                    .collect(),
+                indices: m.mesh.indices,
+                ..Default::default()
+            };
 
-        // We're assuming that the texture files are stored with the obj file
-        let containing_folder = path.as_ref().parent().expect("Directory has no parent");
+            if data.tex_coords.len() != data.GetVertexCount() {
+                log::warn!("{:?}: mesh {:?} has no texture coordinates", path, m.name);
+                data.tex_coords.clear();
+            }
 
-        let mut materials = Vec::new();
-        for mat in obj_materials {
-            let diffuse_path = mat.diffuse_texture;
-            let diffuse_texture =
-                Texture::Load(device, queue, containing_folder.join(diffuse_path))?;
-
-            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
-                    },
-                ],
-                label: None,
+            if data.normals.len() != data.GetVertexCount() {
+                log::warn!(
+                    "{:?}: mesh {:?} has no normals, generating them",
+                    path,
+                    m.name
+                );
+                data.ComputeNormals(options.generated_normals);
+            }
+
+            // tobj leaves the id empty for names missing from the MTL file
+            let material = match m.mesh.material_id {
+                Some(id) if id < obj_materials.len() => Some(id),
+                Some(_) => {
+                    log::warn!("{:?}: mesh {:?} has an unknown material", path, m.name);
+                    None
+                }
+                None => None,
+            };
+
+            meshes.push(LoadedMesh {
+                name: m.name,
+                data,
+                material,
             });
+        }
+
+        Ok((meshes, obj_materials))
+    }
 
-            materials.push(Material {
-                name: mat.name,
-                diffuse_texture,
-                bind_group,
+    /// Loads a glTF 2.0 model, either `.gltf` with embedded or external buffers and
+    /// images, or binary `.glb`.
+    ///
+    /// Every node of the default scene gets its own copy of the primitives of its mesh, baked
+    /// with the world transform of the node, so [`DrawModel`](super::DrawModel) draws the
+    /// scene as laid out. The hierarchy is kept in [`Model::nodes`]. Files without nodes
+    /// keep their primitives in mesh space. Missing normals are generated flat, as the
+    /// specification requires.
+    pub fn LoadGltf<P: AsRef<Path>>(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        generators: &MipmapGeneratorCache,
+        layout: &wgpu::BindGroupLayout,
+        path: P,
+        options: &ModelOptions,
+    ) -> Result<Self, RendererError> {
+        let path = path.as_ref();
+
+        let (document, buffers, images) =
+            gltf::import(path).map_err(|e| RendererError::AssetLoad {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            })?;
+
+        let mut materials = Vec::new();
+        for material in document.materials() {
+            materials.push(Self::LoadGltfMaterial(
+                device, queue, generators, layout, &material, &images,
+            )?);
+        }
+
+        // Primitives without a material use a plain white one, appended when needed
+        let default_material = materials.len();
+
+        let (primitives, mesh_primitives) = Self::LoadGltfMeshes(path, &document, &buffers);
+        let mut nodes = Self::LoadGltfNodes(&document, &mesh_primitives);
+        let primitives = Self::BakeGltfNodes(primitives, &mut nodes);
+
+        let meshes = primitives
+            .into_iter()
+            .map(|primitive| {
+                let material = primitive.material.unwrap_or(default_material);
+                Self::CreateMesh(device, primitive.name, primitive.data, material, options)
+            })
+            .collect::<Vec<_>>();
+
+        if meshes.iter().any(|mesh| mesh.material == default_material) {
+            materials.push(Self::CreateDefaultMaterial(device, queue, layout)?);
+        }
+
+        Ok(Self {
+            meshes,
+            materials,
+            nodes,
+        })
+    }
+
+    /// Reads every primitive with its name and material, skipping invalid ones with a warning.
+    ///
+    /// Also returns the indices of the loaded primitives of each glTF mesh.
+    fn LoadGltfMeshes(
+        path: &Path,
+        document: &gltf::Document,
+        buffers: &[gltf::buffer::Data],
+    ) -> (Vec<LoadedMesh>, Vec<Vec<usize>>) {
+        let mut primitives = Vec::new();
+        let mut mesh_primitives = Vec::new();
+
+        for mesh in document.meshes() {
+            let mut indices = Vec::new();
+
+            for primitive in mesh.primitives() {
+                let name = match mesh.name() {
+                    Some(name) => format!("{} {}", name, primitive.index()),
+                    None => format!("Mesh {} {}", mesh.index(), primitive.index()),
+                };
+
+                match Self::LoadGltfPrimitive(&primitive, buffers) {
+                    Ok(data) => {
+                        indices.push(primitives.len());
+                        primitives.push(LoadedMesh {
+                            name,
+                            data,
+                            material: primitive.material().index(),
+                        });
+                    }
+                    Err(message) => log::warn!("{:?}: skipping {}: {}", path, name, message),
+                }
+            }
+
+            mesh_primitives.push(indices);
+        }
+
+        (primitives, mesh_primitives)
+    }
+
+    /// Flattens the node hierarchy of the default scene, parents before their children.
+    ///
+    /// Nodes reached a second time, through a cycle or a second parent, are skipped with
+    /// a warning.
+    fn LoadGltfNodes(document: &gltf::Document, mesh_primitives: &[Vec<usize>]) -> Vec<ModelNode> {
+        let roots = match document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+        {
+            Some(scene) => scene.nodes().collect::<Vec<_>>(),
+            None => {
+                let children = document
+                    .nodes()
+                    .flat_map(|node| node.children().map(|child| child.index()))
+                    .collect::<HashSet<_>>();
+
+                document
+                    .nodes()
+                    .filter(|node| !children.contains(&node.index()))
+                    .collect()
+            }
+        };
+
+        let mut nodes = Vec::<ModelNode>::new();
+        let mut visited = HashSet::new();
+
+        let mut stack = roots
+            .into_iter()
+            .rev()
+            .map(|node| (node, None::<usize>, Matrix4::identity()))
+            .collect::<Vec<_>>();
+
+        while let Some((node, parent, parent_transform)) = stack.pop() {
+            if !visited.insert(node.index()) {
+                log::warn!("Skipping node {} reached more than once", node.index());
+                continue;
+            }
+
+            let index = nodes.len();
+            let name = node
+                .name()
+                .map_or_else(|| format!("Node {}", node.index()), str::to_string);
+
+            let local_transform = Matrix4::from(node.transform().matrix());
+            let world_transform = parent_transform * local_transform;
+
+            if let Some(parent) = parent {
+                nodes[parent].children.push(index);
+            }
+
+            nodes.push(ModelNode {
+                name,
+                local_transform,
+                world_transform,
+                parent,
+                children: Vec::new(),
+                meshes: node
+                    .mesh()
+                    .map_or_else(Vec::new, |mesh| mesh_primitives[mesh.index()].clone()),
             });
+
+            let children = node.children().collect::<Vec<_>>();
+            for child in children.into_iter().rev() {
+                stack.push((child, Some(index), world_transform));
+            }
         }
 
-        let mut meshes = Vec::new();
-        for m in obj_models {
-            let mut vertices = Vec::new();
-            for i in 0..m.mesh.positions.len() / 3 {
-                vertices.push(ModelVertex {
-                    position: [
-                        m.mesh.positions[i * 3],
-                        m.mesh.positions[i * 3 + 1],
-                        m.mesh.positions[i * 3 + 2],
-                    ],
-                    tex_coords: [m.mesh.texcoords[i * 2], m.mesh.texcoords[i * 2 + 1]],
-                    normal: [
-                        m.mesh.normals[i * 3],
-                        m.mesh.normals[i * 3 + 1],
-                        m.mesh.normals[i * 3 + 2],
-                    ],
+        nodes
+    }
+
+    /// Copies the primitives of every node, transformed by its world transform, and points
+    /// the nodes at their copies. Primitives no node uses are dropped.
+    fn BakeGltfNodes(primitives: Vec<LoadedMesh>, nodes: &mut [ModelNode]) -> Vec<LoadedMesh> {
+        if nodes.is_empty() {
+            return primitives;
+        }
+
+        let mut baked = Vec::new();
+
+        for node in nodes.iter_mut() {
+            for index in &mut node.meshes {
+                let primitive = &primitives[*index];
+
+                let mut data = primitive.data.clone();
+                data.Transform(node.world_transform);
+
+                *index = baked.len();
+                baked.push(LoadedMesh {
+                    name: format!("{} {}", node.name, primitive.name),
+                    data,
+                    material: primitive.material,
                 });
             }
+        }
 
-            let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some(&format!("{:?} Vertex Buffer", path.as_ref())),
-                contents: bytemuck::cast_slice(&vertices),
-                usage: wgpu::BufferUsages::VERTEX,
-            });
-            let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some(&format!("{:?} Index Buffer", path.as_ref())),
-                contents: bytemuck::cast_slice(&m.mesh.indices),
-                usage: wgpu::BufferUsages::INDEX,
-            });
+        baked
+    }
 
-            meshes.push(Mesh {
-                name: m.name,
-                vertex_buffer,
-                index_buffer,
-                num_elements: m.mesh.indices.len() as u32,
-                material: m.mesh.material_id.unwrap_or(0),
-            });
+    fn CreateDefaultMaterial(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Result<Material, RendererError> {
+        let diffuse_texture = Texture::FromColor(device, queue, [255; 4], "Default")?;
+
+        Ok(Material::New(device, layout, "Default", diffuse_texture))
+    }
+
+    fn LoadGltfMaterial(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        generators: &MipmapGeneratorCache,
+        layout: &wgpu::BindGroupLayout,
+        material: &gltf::Material,
+        images: &[gltf::image::Data],
+    ) -> Result<Material, RendererError> {
+        let name = material.name().map_or_else(
+            || format!("Material {}", material.index().unwrap_or_default()),
+            str::to_string,
+        );
+
+        let pbr = material.pbr_metallic_roughness();
+
+        let diffuse_texture = match pbr.base_color_texture() {
+            Some(info) => Self::LoadGltfTexture(
+                device,
+                queue,
+                generators,
+                &images[info.texture().source().index()],
+                &name,
+                &Self::GetGltfTextureOptions(&info.texture(), TextureOptions::default()),
+            )?,
+            None => Texture::FromColor(device, queue, [255; 4], &name)?,
+        };
+
+        let normal_texture = match material.normal_texture() {
+            Some(info) => Some(Self::LoadGltfTexture(
+                device,
+                queue,
+                generators,
+                &images[info.texture().source().index()],
+                &name,
+                &Self::GetGltfTextureOptions(&info.texture(), TextureOptions::Linear()),
+            )?),
+            None => None,
+        };
+
+        let mut result = Material::New(device, layout, name, diffuse_texture);
+        result.normal_texture = normal_texture;
+        result.base_color_factor = pbr.base_color_factor();
+        result.metallic_factor = pbr.metallic_factor();
+        result.roughness_factor = pbr.roughness_factor();
+        result.emissive_factor = material.emissive_factor();
+
+        Ok(result)
+    }
+
+    /// Applies the wrap and filter settings of the sampler of `texture` to `options`.
+    ///
+    /// Minification filters without mipmaps disable them, unset filters keep `options`.
+    fn GetGltfTextureOptions(
+        texture: &gltf::Texture,
+        mut options: TextureOptions,
+    ) -> TextureOptions {
+        use gltf::texture::{MagFilter, MinFilter, WrappingMode};
+
+        let sampler = texture.sampler();
+
+        let address_mode = |mode| match mode {
+            WrappingMode::ClampToEdge => AddressMode::ClampToEdge,
+            WrappingMode::MirroredRepeat => AddressMode::MirrorRepeat,
+            WrappingMode::Repeat => AddressMode::Repeat,
+        };
+
+        options.address_mode_u = address_mode(sampler.wrap_s());
+        options.address_mode_v = address_mode(sampler.wrap_t());
+
+        match sampler.mag_filter() {
+            Some(MagFilter::Nearest) => options.mag_filter = FilterMode::Nearest,
+            Some(MagFilter::Linear) => options.mag_filter = FilterMode::Linear,
+            None => {}
+        }
+
+        let (min_filter, mipmap_filter) = match sampler.min_filter() {
+            Some(MinFilter::Nearest) => (FilterMode::Nearest, None),
+            Some(MinFilter::Linear) => (FilterMode::Linear, None),
+            Some(MinFilter::NearestMipmapNearest) => {
+                (FilterMode::Nearest, Some(FilterMode::Nearest))
+            }
+            Some(MinFilter::LinearMipmapNearest) => (FilterMode::Linear, Some(FilterMode::Nearest)),
+            Some(MinFilter::NearestMipmapLinear) => (FilterMode::Nearest, Some(FilterMode::Linear)),
+            Some(MinFilter::LinearMipmapLinear) => (FilterMode::Linear, Some(FilterMode::Linear)),
+            None => return options,
+        };
+
+        options.min_filter = min_filter;
+
+        match mipmap_filter {
+            Some(mipmap_filter) => options.mipmap_filter = mipmap_filter,
+            None => options.mipmaps = MipmapMode::Disabled,
+        }
+
+        options
+    }
+
+    fn LoadGltfTexture(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        generators: &MipmapGeneratorCache,
+        data: &gltf::image::Data,
+        label: &str,
+        options: &TextureOptions,
+    ) -> Result<Texture, RendererError> {
+        use gltf::image::Format;
+        use image::{DynamicImage, ImageBuffer};
+
+        let (width, height) = (data.width, data.height);
+
+        // Wider formats are stored as native endian bytes
+        let wide = || {
+            data.pixels
+                .chunks_exact(2)
+                .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+                .collect::<Vec<_>>()
+        };
+        let float = || {
+            data.pixels
+                .chunks_exact(4)
+                .map(|c| f32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                .collect::<Vec<_>>()
+        };
+
+        let pixels = data.pixels.clone();
+
+        let img = match data.format {
+            Format::R8 => {
+                ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8)
+            }
+            Format::R8G8 => {
+                ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLumaA8)
+            }
+            Format::R8G8B8 => {
+                ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8)
+            }
+            Format::R8G8B8A8 => {
+                ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8)
+            }
+            Format::R16 => {
+                ImageBuffer::from_raw(width, height, wide()).map(DynamicImage::ImageLuma16)
+            }
+            Format::R16G16 => {
+                ImageBuffer::from_raw(width, height, wide()).map(DynamicImage::ImageLumaA16)
+            }
+            Format::R16G16B16 => {
+                ImageBuffer::from_raw(width, height, wide()).map(DynamicImage::ImageRgb16)
+            }
+            Format::R16G16B16A16 => {
+                ImageBuffer::from_raw(width, height, wide()).map(DynamicImage::ImageRgba16)
+            }
+            Format::R32G32B32FLOAT => {
+                ImageBuffer::from_raw(width, height, float()).map(DynamicImage::ImageRgb32F)
+            }
+            Format::R32G32B32A32FLOAT => {
+                ImageBuffer::from_raw(width, height, float()).map(DynamicImage::ImageRgba32F)
+            }
+        };
+
+        let img = img.ok_or_else(|| RendererError::AssetLoad {
+            path: label.into(),
+            message: format!("image data does not match its {:?} format", data.format),
+        })?;
+
+        Texture::FromImageWith(device, queue, generators, &img, Some(label), options)
+    }
+
+    fn LoadGltfPrimitive(
+        primitive: &gltf::Primitive,
+        buffers: &[gltf::buffer::Data],
+    ) -> Result<MeshData, String> {
+        if primitive.mode() != gltf::mesh::Mode::Triangles {
+            return Err(format!("unsupported primitive mode {:?}", primitive.mode()));
+        }
+
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+        let positions = reader
+            .read_positions()
+            .ok_or("primitive has no positions")?
+            .collect::<Vec<_>>();
+
+        // The vertex holds the UV set the base color texture uses, the other one of the
+        // first two sets stays on the CPU
+        let tex_coord_set = primitive
+            .material()
+            .pbr_metallic_roughness()
+            .base_color_texture()
+            .map_or(0, |info| info.tex_coord());
+
+        let read_tex_coords = |set: u32| {
+            reader
+                .read_tex_coords(set)
+                .map_or_else(Vec::new, |tex_coords| tex_coords.into_f32().collect())
+        };
+
+        let indices = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+
+        let mut data = MeshData {
+            normals: reader
+                .read_normals()
+                .map_or_else(Vec::new, Iterator::collect),
+            tex_coords: read_tex_coords(tex_coord_set),
+            tex_coords_1: read_tex_coords(if tex_coord_set == 1 { 0 } else { 1 }),
+            tangents: reader
+                .read_tangents()
+                .map_or_else(Vec::new, Iterator::collect),
+            colors: reader
+                .read_colors(0)
+                .map_or_else(Vec::new, |colors| colors.into_rgba_f32().collect()),
+            positions,
+            indices,
+        };
+
+        if let Some(index) = data
+            .indices
+            .iter()
+            .find(|&&i| i as usize >= data.GetVertexCount())
+        {
+            return Err(format!("index {} is out of bounds", index));
+        }
+
+        if data.normals.is_empty() {
+            data.ComputeNormals(NormalMode::Flat);
         }
 
-        Ok(Self { meshes, materials })
+        Ok(data)
+    }
+
+    /// Uploads the geometry, generating the tangents first when requested.
+    fn CreateMesh(
+        device: &wgpu::Device,
+        name: String,
+        mut data: MeshData,
+        material: usize,
+        options: &ModelOptions,
+    ) -> Mesh {
+        if options.generate_tangents && data.tangents.is_empty() {
+            data.ComputeTangents();
+        }
+
+        let mut mesh = data.Upload(device, name, material);
+
+        if options.keep_mesh_data {
+            mesh.data = Some(data);
+        }
+
+        mesh
     }
 }
 
+/// Geometry read from a model file, before it is uploaded.
+struct LoadedMesh {
+    name: String,
+    data: MeshData,
+    /// Material index, `None` for the default material.
+    material: Option<usize>,
+}
+
 #[repr(C)]
-#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable, Vertex)]
 pub struct ModelVertex {
-    position: [f32; 3],
-    tex_coords: [f32; 2],
-    normal: [f32; 3],
+    pub position: [f32; 3],
+    pub tex_coords: [f32; 2],
+    pub normal: [f32; 3],
+    /// Tangent direction, `w` holds the handedness of the bitangent.
+    pub tangent: [f32; 4],
+}
+
+impl ModelVertex {
+    /// Tangent used when the source provides none.
+    pub const DEFAULT_TANGENT: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
 }
 
-impl Vertex for ModelVertex {
-    fn GetDescriptor<'a>() -> wgpu::VertexBufferLayout<'a> {
-        use std::mem;
-        wgpu::VertexBufferLayout {
-            array_stride: mem::size_of::<ModelVertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-                wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x2,
-                },
-                wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 5]>() as wgpu::BufferAddress,
-                    shader_location: 2,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-            ],
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Render::{StepMode, VertexFormat};
+    use cgmath::{Vector3, Vector4};
+
+    #[test]
+    fn DerivedVertexLayoutMatchesFields() {
+        let layout = ModelVertex::GetLayout();
+
+        assert_eq!(layout.stride, 48);
+        assert_eq!(layout.step_mode, StepMode::Vertex);
+        assert_eq!(
+            layout
+                .attributes
+                .iter()
+                .map(|a| (a.format, a.offset, a.shader_location))
+                .collect::<Vec<_>>(),
+            vec![
+                (VertexFormat::Float32x3, 0, 0),
+                (VertexFormat::Float32x2, 12, 1),
+                (VertexFormat::Float32x3, 20, 2),
+                (VertexFormat::Float32x4, 32, 3),
+            ]
+        );
+    }
+
+    fn ImportGltf(name: &str) -> (gltf::Document, Vec<LoadedMesh>, Vec<ModelNode>) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("Content")
+            .join(name);
+        let (document, buffers, _) = gltf::import(&path).unwrap();
+
+        let (primitives, mesh_primitives) = Model::LoadGltfMeshes(&path, &document, &buffers);
+        let nodes = Model::LoadGltfNodes(&document, &mesh_primitives);
+
+        (document, primitives, nodes)
+    }
+
+    const TRIANGLE: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+
+    #[test]
+    fn GltfNodesAreOrderedParentsFirst() {
+        let (_, _, nodes) = ImportGltf("SM_Hierarchy.gltf");
+
+        assert_eq!(
+            nodes.iter().map(|n| n.name.as_str()).collect::<Vec<_>>(),
+            ["Root", "Left", "Mirrored", "Flat", "Broken"]
+        );
+        assert_eq!(
+            nodes.iter().map(|n| n.parent).collect::<Vec<_>>(),
+            [None, Some(0), Some(0), Some(2), Some(0)]
+        );
+        assert_eq!(nodes[0].children, [1, 2, 4]);
+        assert_eq!(nodes[2].children, [3]);
+
+        // Flat inherits the translation of Root and the mirroring of its parent
+        assert_eq!(
+            nodes[3].world_transform,
+            Matrix4::from_translation(Vector3::new(1.0, 2.0, 3.0))
+                * Matrix4::from_nonuniform_scale(-1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn GltfMeshesAreSharedBetweenNodes() {
+        let (_, primitives, nodes) = ImportGltf("SM_Hierarchy.gltf");
+
+        // The broken mesh is skipped, its node keeps no meshes
+        assert_eq!(primitives.len(), 2);
+        assert_eq!(primitives[0].name, "Triangle 0");
+        assert_eq!(primitives[0].material, Some(0));
+        assert_eq!(primitives[1].material, None);
+
+        assert_eq!(nodes[1].meshes, [0]);
+        assert_eq!(nodes[2].meshes, [0]);
+        assert_eq!(nodes[3].meshes, [1]);
+        assert!(nodes[4].meshes.is_empty());
+        assert!(nodes[0].meshes.is_empty());
+    }
+
+    #[test]
+    fn GltfMeshesStayInMeshSpace() {
+        let (_, primitives, nodes) = ImportGltf("SM_Hierarchy.gltf");
+        let data = &primitives[0].data;
+
+        assert!(!nodes[1].IsMirrored());
+        assert!(nodes[2].IsMirrored());
+        assert!(nodes[3].IsMirrored());
+
+        // Neither the translation nor the mirroring is baked, the winding is unchanged
+        assert_eq!(data.positions, TRIANGLE);
+        assert_eq!(data.indices, [0, 1, 2]);
+        assert_eq!(data.normals, [[0.0, 0.0, 1.0]; 3]);
+
+        let mirrored = nodes[2].world_transform * Vector4::new(1.0, 0.0, 0.0, 1.0);
+        assert_eq!(mirrored, Vector4::new(0.0, 2.0, 3.0, 1.0));
+    }
+
+    #[test]
+    fn GltfNodesBakeTheirTransforms() {
+        let (_, primitives, mut nodes) = ImportGltf("SM_Hierarchy.gltf");
+        let baked = Model::BakeGltfNodes(primitives, &mut nodes);
+
+        // One copy per node using a mesh, the broken mesh has none
+        assert_eq!(
+            baked.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(),
+            ["Left Triangle 0", "Mirrored Triangle 0", "Flat Flat 0"]
+        );
+        assert_eq!(nodes[1].meshes, [0]);
+        assert_eq!(nodes[2].meshes, [1]);
+        assert_eq!(nodes[3].meshes, [2]);
+        assert_eq!(baked[0].material, Some(0));
+        assert_eq!(baked[2].material, None);
+
+        assert_eq!(
+            baked[0].data.positions,
+            [[1.0, 2.0, 3.0], [2.0, 2.0, 3.0], [1.0, 3.0, 3.0]]
+        );
+        assert_eq!(baked[0].data.indices, [0, 1, 2]);
+
+        // Mirrored copies keep facing outwards
+        for mirrored in &baked[1..] {
+            assert_eq!(
+                mirrored.data.positions,
+                [[1.0, 2.0, 3.0], [0.0, 2.0, 3.0], [1.0, 3.0, 3.0]]
+            );
+            assert_eq!(mirrored.data.indices, [0, 2, 1]);
+            assert_eq!(mirrored.data.normals, [[0.0, 0.0, 1.0]; 3]);
         }
     }
+
+    #[test]
+    fn GltfWithoutNodesKeepsMeshSpace() {
+        let (_, primitives, _) = ImportGltf("SM_Hierarchy.gltf");
+        let baked = Model::BakeGltfNodes(primitives, &mut []);
+
+        assert_eq!(baked.len(), 2);
+        assert_eq!(baked[0].data.positions, TRIANGLE);
+    }
+
+    #[test]
+    fn GltfNodeCyclesAreSkipped() {
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "scene": 0,
+            "scenes": [{ "nodes": [0] }],
+            "nodes": [
+                { "name": "A", "children": [1] },
+                { "name": "B", "children": [0, 2] },
+                { "name": "C", "children": [1] }
+            ]
+        }"#;
+        let document = gltf::Gltf::from_slice(json.as_bytes()).unwrap().document;
+
+        let nodes = Model::LoadGltfNodes(&document, &[]);
+
+        assert_eq!(
+            nodes.iter().map(|n| n.name.as_str()).collect::<Vec<_>>(),
+            ["A", "B", "C"]
+        );
+        assert_eq!(nodes[0].children, [1]);
+        assert_eq!(nodes[1].children, [2]);
+        assert!(nodes[2].children.is_empty());
+    }
+
+    #[test]
+    fn GltfMissingNormalsAreFlat() {
+        let (_, primitives, _) = ImportGltf("SM_Hierarchy.gltf");
+        let data = &primitives[1].data;
+
+        assert_eq!(data.positions, TRIANGLE);
+        assert_eq!(data.normals, [[0.0, 0.0, 1.0]; 3]);
+        assert!(data.tex_coords.is_empty());
+    }
+
+    #[test]
+    fn GltfVertexUsesBaseColorUvSet() {
+        let (_, primitives, _) = ImportGltf("SM_Hierarchy.gltf");
+        let data = &primitives[0].data;
+
+        // The base color texture samples TEXCOORD_1
+        assert_eq!(data.tex_coords, [[0.5, 0.5], [1.0, 0.5], [0.5, 1.0]]);
+        assert_eq!(data.tex_coords_1, [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+        assert_eq!(data.GetVertices()[1].tex_coords, [1.0, 0.5]);
+    }
+
+    #[test]
+    fn GltfSamplerSettingsApply() {
+        let (document, _, _) = ImportGltf("SM_Hierarchy.gltf");
+        let texture = document.textures().next().unwrap();
+
+        let options = Model::GetGltfTextureOptions(&texture, TextureOptions::default());
+
+        assert_eq!(options.address_mode_u, AddressMode::MirrorRepeat);
+        assert_eq!(options.address_mode_v, AddressMode::ClampToEdge);
+        assert_eq!(options.mag_filter, FilterMode::Nearest);
+        assert_eq!(options.min_filter, FilterMode::Linear);
+        // LINEAR without a mipmap mode samples the base level only
+        assert_eq!(options.mipmaps, MipmapMode::Disabled);
+        assert!(options.srgb);
+    }
+
+    #[test]
+    fn GltfDefaultSamplerRepeats() {
+        let (document, _, _) = ImportGltf("SM_Hierarchy.gltf");
+        let mut json = document.into_json();
+        json.samplers.clear();
+        json.textures[0].sampler = None;
+
+        let document = gltf::Document::from_json(json).unwrap();
+        let texture = document.textures().next().unwrap();
+        let options = Model::GetGltfTextureOptions(&texture, TextureOptions::Linear());
+
+        assert_eq!(options.address_mode_u, AddressMode::Repeat);
+        assert_eq!(options.address_mode_v, AddressMode::Repeat);
+        assert_eq!(options.mipmaps, MipmapMode::default());
+        assert!(!options.srgb);
+    }
+
+    #[test]
+    fn GlbLoads() {
+        let (_, primitives, nodes) = ImportGltf("SM_Triangle.glb");
+
+        assert_eq!(primitives.len(), 1);
+        assert_eq!(primitives[0].data.positions, TRIANGLE);
+        assert_eq!(primitives[0].data.tex_coords.len(), 3);
+        assert!(primitives[0].data.tex_coords_1.is_empty());
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].meshes, [0]);
+    }
+
+    fn ImportObj(name: &str) -> (Vec<LoadedMesh>, Vec<tobj::Material>) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("Content")
+            .join(name);
+
+        Model::LoadObjMeshes(&path, &ModelOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn ObjWithoutNormalsGeneratesThem() {
+        let (meshes, materials) = ImportObj("SM_QuadNoNormals.obj");
+
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].data.GetTriangleCount(), 2);
+        assert_eq!(
+            meshes[0].data.normals,
+            vec![[0.0, 0.0, 1.0]; meshes[0].data.GetVertexCount()]
+        );
+        assert_eq!(
+            meshes[0].data.tex_coords.len(),
+            meshes[0].data.GetVertexCount()
+        );
+
+        // A material without map_Kd is still used, with a white texture
+        assert_eq!(materials[1].name, "Plain");
+        assert!(materials[1].diffuse_texture.is_empty());
+        assert_eq!(meshes[0].material, Some(1));
+    }
+
+    #[test]
+    fn ObjWithoutTexCoordsLeavesThemEmpty() {
+        let (meshes, _) = ImportObj("SM_QuadNoTexCoords.obj");
+        let data = &meshes[0].data;
+
+        assert!(data.tex_coords.is_empty());
+        assert_eq!(data.normals, vec![[0.0, 0.0, 1.0]; data.GetVertexCount()]);
+        assert!(data
+            .GetVertices()
+            .iter()
+            .all(|v| v.tex_coords == [0.0, 0.0]));
+        assert_eq!(meshes[0].material, Some(0));
+    }
+
+    #[test]
+    fn ObjUnknownMaterialUsesDefault() {
+        let (meshes, materials) = ImportObj("SM_QuadUnknownMaterial.obj");
+
+        // LoadObj gives it the default material, appended after the two of the MTL file
+        assert_eq!(materials.len(), 2);
+        assert_eq!(meshes[0].material, None);
+    }
+
+    #[test]
+    fn ObjAssetsResolveNextToTheModel() {
+        let resolve = |path: &str| Model::GetObjAssetPath(Path::new(path), "T_White.png");
+
+        assert_eq!(resolve("SM_Cube.obj"), Path::new("T_White.png"));
+        assert_eq!(
+            resolve("Content/SM_Cube.obj"),
+            Path::new("Content/T_White.png")
+        );
+        assert_eq!(
+            resolve("/Content/SM_Cube.obj"),
+            Path::new("/Content/T_White.png")
+        );
+        assert_eq!(resolve(""), Path::new("T_White.png"));
+    }
+
+    #[test]
+    fn ObjRelativePathLoads() {
+        // Tests run in the manifest directory, no need to change the working directory
+        let path = Path::new("Content/SM_Cube.obj");
+        let (meshes, materials) = Model::LoadObjMeshes(path, &ModelOptions::default()).unwrap();
+
+        assert_eq!(meshes[0].material, Some(0));
+        assert_eq!(materials[0].diffuse_texture, "T_White.png");
+        assert!(Model::GetObjAssetPath(path, &materials[0].diffuse_texture).is_file());
+    }
 }
@@ -0,0 +1,28 @@
+use cgmath::{Matrix4, SquareMatrix};
+
+/// Node of the scene hierarchy of a [`Model`](super::Model).
+#[derive(Clone, Debug)]
+pub struct ModelNode {
+    pub name: String,
+
+    /// Transform relative to the parent node.
+    pub local_transform: Matrix4<f32>,
+
+    /// Transform relative to the model, already applied to the vertices of its meshes.
+    pub world_transform: Matrix4<f32>,
+
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+
+    /// Indices into [`Model::meshes`](super::Model::meshes), one per primitive, each a copy
+    /// baked with the world transform of this node.
+    pub meshes: Vec<usize>,
+}
+
+impl ModelNode {
+    /// Whether the world transform mirrors the node. Baking flips the winding of its meshes
+    /// back, so they draw with the usual [`FrontFace`](super::FrontFace) and culling.
+    pub fn IsMirrored(&self) -> bool {
+        self.world_transform.determinant() < 0.0
+    }
+}
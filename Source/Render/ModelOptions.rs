@@ -0,0 +1,26 @@
+use super::NormalMode;
+use serde::{Deserialize, Serialize};
+
+/// Settings used by [`Model::LoadWith`](super::Model::LoadWith).
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelOptions {
+    /// Normals generated for meshes without them.
+    pub generated_normals: NormalMode,
+
+    /// Generates MikkTSpace tangents for meshes that have texture coordinates but no tangents.
+    pub generate_tangents: bool,
+
+    /// Keeps the CPU geometry in [`Mesh::data`](super::Mesh::data) after uploading it.
+    pub keep_mesh_data: bool,
+}
+
+impl Default for ModelOptions {
+    fn default() -> Self {
+        Self {
+            generated_normals: NormalMode::default(),
+            generate_tangents: true,
+            keep_mesh_data: false,
+        }
+    }
+}
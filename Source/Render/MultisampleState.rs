@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MultisampleState {
+    /// Number of samples per pixel, 1 disables multisampling.
+    pub count: u32,
+
+    /// Bitmask of the samples that are active.
+    pub mask: u64,
+
+    pub alpha_to_coverage_enabled: bool,
+}
+
+impl Default for MultisampleState {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        }
+    }
+}
+
+impl From<MultisampleState> for wgpu::MultisampleState {
+    fn from(state: MultisampleState) -> Self {
+        wgpu::MultisampleState {
+            count: state.count,
+            mask: state.mask,
+            alpha_to_coverage_enabled: state.alpha_to_coverage_enabled,
+        }
+    }
+}
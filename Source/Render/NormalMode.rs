@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// How normals are generated for meshes that don't provide them.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum NormalMode {
+    /// Every face uses its own normal, vertices shared between faces are split.
+    Flat = 0,
+
+    /// Shared vertices average the normals of the adjacent faces, weighted by area.
+    Smooth = 1,
+}
+
+impl Default for NormalMode {
+    fn default() -> Self {
+        Self::Smooth
+    }
+}
@@ -0,0 +1,642 @@
+use super::{
+    BindGroupLayout, PipelineCacheStats, PipelineDescriptor, PipelineHandle, RendererError,
+};
+use crate::Shader::{Shader, ShaderSource};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Everything a cached pipeline is built from.
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+struct PipelineKey {
+    /// Descriptor the pipeline was requested with, without its label and with its shaders
+    /// reduced to [`GetSourceKey`].
+    descriptor: PipelineDescriptor,
+    /// Preprocessed shader sources, covering included files.
+    sources: Vec<String>,
+    /// Entries of the bind group layouts.
+    bind_group_layouts: Vec<Vec<wgpu::BindGroupLayoutEntry>>,
+    format: wgpu::TextureFormat,
+}
+
+struct CachedPipeline {
+    /// Descriptor the current pipeline was built from, differs after a hot reload.
+    current: PipelineDescriptor,
+    layout: Arc<wgpu::PipelineLayout>,
+    handle: PipelineHandle,
+    files: Vec<PathBuf>,
+}
+
+#[derive(Default)]
+struct PipelineCacheInner {
+    pipelines: HashMap<PipelineKey, CachedPipeline>,
+    /// Preprocessed sources by [`GetSourceKey`], read once and replaced on reload.
+    sources: HashMap<Shader, String>,
+    hits: u64,
+    misses: u64,
+}
+
+/// Render pipelines shared between states, keyed by their descriptor, preprocessed shader
+/// sources, bind group layouts and color target format.
+///
+/// The label is not part of the key. Shader files are preprocessed on first use and again
+/// when they are reloaded, so a descriptor keeps finding its pipeline after a hot reload.
+/// Pipelines are built without holding the cache lock.
+#[derive(Default)]
+pub struct PipelineCache {
+    inner: Mutex<PipelineCacheInner>,
+}
+
+impl PipelineCache {
+    pub fn New() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached pipeline for the descriptor, building it and its layout with `create` on a miss.
+    pub fn GetOrCreate(
+        &self,
+        descriptor: &PipelineDescriptor,
+        bind_group_layouts: &[&BindGroupLayout],
+        format: wgpu::TextureFormat,
+        create: impl FnOnce() -> Result<(wgpu::RenderPipeline, wgpu::PipelineLayout), RendererError>,
+    ) -> Result<PipelineHandle, RendererError> {
+        let bind_group_layouts = bind_group_layouts
+            .iter()
+            .map(|layout| layout.GetEntries())
+            .collect::<Vec<_>>();
+
+        let key = self.GetKey(descriptor, &bind_group_layouts, format)?;
+
+        {
+            let mut inner = self.inner.lock().unwrap();
+
+            if let Some(cached) = inner.pipelines.get(&key) {
+                let handle = cached.handle.clone();
+                inner.hits += 1;
+                return Ok(handle);
+            }
+
+            inner.misses += 1;
+        }
+
+        let (pipeline, layout) = create()?;
+        let files = descriptor.GetShaderFiles();
+
+        // Another thread may have built the same pipeline meanwhile, keep the handle it got
+        let mut inner = self.inner.lock().unwrap();
+        let cached = inner
+            .pipelines
+            .entry(key)
+            .or_insert_with(|| CachedPipeline {
+                current: descriptor.clone(),
+                layout: Arc::new(layout),
+                handle: PipelineHandle::New(pipeline),
+                files,
+            });
+
+        Ok(cached.handle.clone())
+    }
+
+    fn GetKey(
+        &self,
+        descriptor: &PipelineDescriptor,
+        bind_group_layouts: &[&[wgpu::BindGroupLayoutEntry]],
+        format: wgpu::TextureFormat,
+    ) -> Result<PipelineKey, RendererError> {
+        let mut sources = vec![self.GetSource(&descriptor.shader)?];
+
+        if let Some(shader) = &descriptor.fragment_shader {
+            sources.push(self.GetSource(shader)?);
+        }
+
+        Ok(PipelineKey {
+            descriptor: PipelineDescriptor {
+                label: "".into(),
+                shader: GetSourceKey(&descriptor.shader),
+                fragment_shader: descriptor.fragment_shader.as_ref().map(GetSourceKey),
+                ..descriptor.clone()
+            },
+            sources,
+            bind_group_layouts: bind_group_layouts
+                .iter()
+                .map(|entries| entries.to_vec())
+                .collect(),
+            format,
+        })
+    }
+
+    /// Returns the preprocessed source of `shader`, preprocessing it on first use only.
+    fn GetSource(&self, shader: &Shader) -> Result<String, RendererError> {
+        let key = GetSourceKey(shader);
+
+        if let Some(source) = self.inner.lock().unwrap().sources.get(&key) {
+            return Ok(source.clone());
+        }
+
+        let source = shader.Preprocess()?.code;
+
+        Ok(self
+            .inner
+            .lock()
+            .unwrap()
+            .sources
+            .entry(key)
+            .or_insert(source)
+            .clone())
+    }
+
+    /// Shader files the cached pipelines are built from.
+    pub fn GetWatchedFiles(&self) -> Vec<PathBuf> {
+        let inner = self.inner.lock().unwrap();
+
+        let mut files = inner
+            .pipelines
+            .values()
+            .flat_map(|cached| cached.files.iter().cloned())
+            .collect::<Vec<_>>();
+
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    /// Rebuilds every pipeline using one of the changed files with `rebuild` and swaps
+    /// it into its handle.
+    ///
+    /// A pipeline that fails to rebuild keeps the previous one and the error is logged.
+    /// Returns the number of pipelines replaced.
+    pub fn Reload(
+        &self,
+        changed: &[PathBuf],
+        rebuild: impl Fn(
+            &PipelineDescriptor,
+            &wgpu::PipelineLayout,
+        ) -> Result<wgpu::RenderPipeline, RendererError>,
+    ) -> usize {
+        // Rebuilt without holding the lock, lookups from other threads don't wait on them
+        let stale = self
+            .inner
+            .lock()
+            .unwrap()
+            .pipelines
+            .iter()
+            .filter(|(_, cached)| cached.files.iter().any(|file| changed.contains(file)))
+            .map(|(key, cached)| (key.clone(), cached.current.clone(), cached.layout.clone()))
+            .collect::<Vec<_>>();
+
+        let mut reloaded = 0;
+
+        for (key, mut descriptor, layout) in stale {
+            let result = descriptor.ReloadShaders().and_then(|_| {
+                let mut sources = vec![descriptor.shader.Preprocess()?.code];
+
+                if let Some(shader) = &descriptor.fragment_shader {
+                    sources.push(shader.Preprocess()?.code);
+                }
+
+                Ok((rebuild(&descriptor, &layout)?, sources))
+            });
+
+            let (pipeline, sources) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    log::error!(
+                        "Failed to reload pipeline {:?}, keeping the previous one: {}",
+                        descriptor.label,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            log::info!("Reloaded pipeline {:?}", descriptor.label);
+
+            let files = descriptor.GetShaderFiles();
+            let mut inner = self.inner.lock().unwrap();
+
+            // Cleared while rebuilding
+            let mut cached = match inner.pipelines.remove(&key) {
+                Some(cached) => cached,
+                None => continue,
+            };
+
+            cached.handle.Set(pipeline);
+            cached.files = files;
+
+            // Later lookups see the new sources and find the entry under its new key
+            let shaders = std::iter::once(&descriptor.shader).chain(&descriptor.fragment_shader);
+            for (shader, source) in shaders.zip(&sources) {
+                inner.sources.insert(GetSourceKey(shader), source.clone());
+            }
+
+            cached.current = descriptor;
+            inner
+                .pipelines
+                .insert(PipelineKey { sources, ..key }, cached);
+            reloaded += 1;
+        }
+
+        reloaded
+    }
+
+    pub fn GetStats(&self) -> PipelineCacheStats {
+        let inner = self.inner.lock().unwrap();
+
+        PipelineCacheStats {
+            hits: inner.hits,
+            misses: inner.misses,
+            pipelines: inner.pipelines.len(),
+        }
+    }
+
+    /// Drops every cached pipeline and source, the statistics are kept.
+    pub fn Clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.pipelines.clear();
+        inner.sources.clear();
+    }
+}
+
+/// Identifies the preprocessed source of `shader`. Shaders loaded from a file are identified
+/// by their path, the cache tracks their current source itself.
+fn GetSourceKey(shader: &Shader) -> Shader {
+    let mut shader = Shader {
+        label: "".into(),
+        ..shader.clone()
+    };
+
+    if shader.path.is_some() {
+        shader.source = ShaderSource::Wgsl(String::new());
+    }
+
+    shader
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Render::Renderer;
+    use crate::Shader::Shader;
+    use std::fs;
+
+    const SHADER: &str = "
+        @vertex
+        fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+            return vec4<f32>(f32(index), 0.0, 0.0, 1.0);
+        }
+
+        @fragment
+        fn fs_main() -> @location(0) vec4<f32> {
+            return vec4<f32>(1.0);
+        }
+    ";
+
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    fn Uniform(binding: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }
+    }
+
+    fn Key(
+        descriptor: &PipelineDescriptor,
+        bind_group_layouts: &[&[wgpu::BindGroupLayoutEntry]],
+    ) -> PipelineKey {
+        PipelineCache::New()
+            .GetKey(descriptor, bind_group_layouts, FORMAT)
+            .unwrap()
+    }
+
+    #[test]
+    fn KeyIgnoresLabel() {
+        let descriptor = PipelineDescriptor::New(Shader::FromWgsl(SHADER));
+        let labeled = PipelineDescriptor {
+            label: "Labeled".into(),
+            ..descriptor.clone()
+        };
+
+        assert_eq!(Key(&descriptor, &[]), Key(&labeled, &[]));
+    }
+
+    #[test]
+    fn KeyIncludesBindGroupLayoutsAndFormat() {
+        let descriptor = PipelineDescriptor::New(Shader::FromWgsl(SHADER));
+
+        assert_eq!(
+            Key(&descriptor, &[&[Uniform(0)]]),
+            Key(&descriptor, &[&[Uniform(0)]])
+        );
+        assert_ne!(
+            Key(&descriptor, &[&[Uniform(0)]]),
+            Key(&descriptor, &[&[Uniform(1)]])
+        );
+        assert_ne!(
+            Key(&descriptor, &[&[Uniform(0)]]),
+            Key(&descriptor, &[&[Uniform(0)], &[]])
+        );
+        assert_ne!(
+            Key(&descriptor, &[]),
+            PipelineCache::New()
+                .GetKey(&descriptor, &[], wgpu::TextureFormat::Bgra8Unorm)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn KeyIncludesIncludedFiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let common = dir.path().join("Common.wgsl");
+        let main = dir.path().join("Main.wgsl");
+
+        fs::write(&main, "#include \"Common.wgsl\"\n").unwrap();
+        fs::write(&common, "let SCALE: f32 = 1.0;\n").unwrap();
+
+        let descriptor = PipelineDescriptor::New(Shader::FromFile(&main).unwrap());
+        let before = Key(&descriptor, &[]);
+
+        fs::write(&common, "let SCALE: f32 = 2.0;\n").unwrap();
+
+        assert_ne!(before, Key(&descriptor, &[]));
+    }
+
+    #[test]
+    fn KeyReusesPreprocessedSources() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Shader.wgsl");
+        fs::write(&file, SHADER).unwrap();
+
+        let cache = PipelineCache::New();
+        let descriptor = PipelineDescriptor::New(Shader::FromFile(&file).unwrap());
+        let before = cache.GetKey(&descriptor, &[], FORMAT).unwrap();
+
+        // Only a reload reads the file again, a descriptor loaded afterwards has the same key
+        fs::write(&file, SHADER.replace("1.0", "0.5")).unwrap();
+        let reloaded = PipelineDescriptor::New(Shader::FromFile(&file).unwrap());
+
+        assert_eq!(before, cache.GetKey(&descriptor, &[], FORMAT).unwrap());
+        assert_eq!(before, cache.GetKey(&reloaded, &[], FORMAT).unwrap());
+    }
+
+    fn Create(
+        renderer: &Renderer,
+        descriptor: &PipelineDescriptor,
+    ) -> Result<(wgpu::RenderPipeline, wgpu::PipelineLayout), RendererError> {
+        let layout = renderer.CreatePipelineLayout(&descriptor.label, &[]);
+        let pipeline = renderer.CreatePipelineWithLayout(descriptor, &layout)?;
+
+        Ok((pipeline, layout))
+    }
+
+    #[test]
+    #[ignore = "needs an adapter"]
+    fn ReloadKeepsHandlesValid() {
+        let renderer = Renderer::NewForTests();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Shader.wgsl");
+        fs::write(&file, SHADER).unwrap();
+
+        let cache = PipelineCache::New();
+        let descriptor = PipelineDescriptor::New(Shader::FromFile(&file).unwrap());
+        let handle = cache
+            .GetOrCreate(&descriptor, &[], FORMAT, || Create(&renderer, &descriptor))
+            .unwrap();
+        let copy = handle.clone();
+        let previous = handle.Get();
+
+        fs::write(&file, SHADER.replace("1.0", "0.5")).unwrap();
+
+        assert_eq!(
+            cache.Reload(&cache.GetWatchedFiles(), |descriptor, layout| {
+                renderer.CreatePipelineWithLayout(descriptor, layout)
+            }),
+            1
+        );
+        assert!(!Arc::ptr_eq(&previous, &handle.Get()));
+        assert!(Arc::ptr_eq(&handle.Get(), &copy.Get()));
+    }
+
+    #[test]
+    #[ignore = "needs an adapter"]
+    fn FailedReloadKeepsPipeline() {
+        let renderer = Renderer::NewForTests();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Shader.wgsl");
+        fs::write(&file, SHADER).unwrap();
+
+        let cache = PipelineCache::New();
+        let descriptor = PipelineDescriptor::New(Shader::FromFile(&file).unwrap());
+        let handle = cache
+            .GetOrCreate(&descriptor, &[], FORMAT, || Create(&renderer, &descriptor))
+            .unwrap();
+        let previous = handle.Get();
+
+        fs::write(&file, "not a shader").unwrap();
+
+        assert_eq!(
+            cache.Reload(&cache.GetWatchedFiles(), |descriptor, layout| {
+                renderer.CreatePipelineWithLayout(descriptor, layout)
+            }),
+            0
+        );
+        assert!(Arc::ptr_eq(&previous, &handle.Get()));
+
+        // Fixing the file afterwards still reloads the same handle
+        fs::write(&file, SHADER.replace("1.0", "0.5")).unwrap();
+
+        assert_eq!(
+            cache.Reload(&cache.GetWatchedFiles(), |descriptor, layout| {
+                renderer.CreatePipelineWithLayout(descriptor, layout)
+            }),
+            1
+        );
+        assert!(!Arc::ptr_eq(&previous, &handle.Get()));
+    }
+
+    #[test]
+    #[ignore = "needs an adapter"]
+    fn ReloadSkipsUnchangedPipelines() {
+        let renderer = Renderer::NewForTests();
+
+        let cache = PipelineCache::New();
+        let descriptor = PipelineDescriptor::New(Shader::FromWgsl(SHADER));
+        let handle = cache
+            .GetOrCreate(&descriptor, &[], FORMAT, || Create(&renderer, &descriptor))
+            .unwrap();
+        let previous = handle.Get();
+
+        let changed = [PathBuf::from("Other.wgsl")];
+
+        assert_eq!(
+            cache.Reload(&changed, |descriptor, layout| {
+                renderer.CreatePipelineWithLayout(descriptor, layout)
+            }),
+            0
+        );
+        assert!(Arc::ptr_eq(&previous, &handle.Get()));
+    }
+
+    #[test]
+    #[ignore = "needs an adapter"]
+    fn HitsAndMissesAreCounted() {
+        let renderer = Renderer::NewForTests();
+
+        let cache = PipelineCache::New();
+        let descriptor = PipelineDescriptor::New(Shader::FromWgsl(SHADER));
+        let labeled = PipelineDescriptor {
+            label: "Labeled".into(),
+            ..descriptor.clone()
+        };
+        let culled = PipelineDescriptor {
+            cull_mode: crate::Render::CullMode::Back,
+            ..descriptor.clone()
+        };
+
+        for descriptor in [&descriptor, &labeled, &culled, &culled] {
+            cache
+                .GetOrCreate(descriptor, &[], FORMAT, || Create(&renderer, descriptor))
+                .unwrap();
+        }
+
+        assert_eq!(
+            cache.GetStats(),
+            PipelineCacheStats {
+                hits: 2,
+                misses: 2,
+                pipelines: 2,
+            }
+        );
+    }
+
+    #[test]
+    #[ignore = "needs an adapter"]
+    fn ReloadedPipelinesAreFoundByTheirDescriptor() {
+        let renderer = Renderer::NewForTests();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Shader.wgsl");
+        fs::write(&file, SHADER).unwrap();
+
+        let cache = PipelineCache::New();
+        let descriptor = PipelineDescriptor::New(Shader::FromFile(&file).unwrap());
+        let handle = cache
+            .GetOrCreate(&descriptor, &[], FORMAT, || Create(&renderer, &descriptor))
+            .unwrap();
+
+        fs::write(&file, SHADER.replace("1.0", "0.5")).unwrap();
+
+        assert_eq!(
+            cache.Reload(&cache.GetWatchedFiles(), |descriptor, layout| {
+                renderer.CreatePipelineWithLayout(descriptor, layout)
+            }),
+            1
+        );
+
+        // Both the old descriptor and one loaded after the reload hit the reloaded pipeline
+        let reloaded = PipelineDescriptor::New(Shader::FromFile(&file).unwrap());
+
+        for descriptor in [&descriptor, &reloaded] {
+            let found = cache
+                .GetOrCreate(descriptor, &[], FORMAT, || Create(&renderer, descriptor))
+                .unwrap();
+
+            assert!(Arc::ptr_eq(&handle.Get(), &found.Get()));
+        }
+
+        assert_eq!(
+            cache.GetStats(),
+            PipelineCacheStats {
+                hits: 2,
+                misses: 1,
+                pipelines: 1,
+            }
+        );
+    }
+
+    #[test]
+    #[ignore = "needs an adapter"]
+    fn ConcurrentMissKeepsFirstHandle() {
+        let renderer = Renderer::NewForTests();
+
+        let cache = PipelineCache::New();
+        let descriptor = PipelineDescriptor::New(Shader::FromWgsl(SHADER));
+
+        // The inner call stands in for another thread building the same pipeline meanwhile
+        let mut first = None;
+        let handle = cache
+            .GetOrCreate(&descriptor, &[], FORMAT, || {
+                first = Some(
+                    cache
+                        .GetOrCreate(&descriptor, &[], FORMAT, || Create(&renderer, &descriptor))
+                        .unwrap(),
+                );
+
+                Create(&renderer, &descriptor)
+            })
+            .unwrap();
+
+        assert!(Arc::ptr_eq(&first.unwrap().Get(), &handle.Get()));
+        assert_eq!(cache.GetStats().pipelines, 1);
+    }
+
+    #[test]
+    #[ignore = "needs an adapter"]
+    fn ReloadDoesNotHoldTheLock() {
+        let renderer = Renderer::NewForTests();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Shader.wgsl");
+        fs::write(&file, SHADER).unwrap();
+
+        let cache = PipelineCache::New();
+        let descriptor = PipelineDescriptor::New(Shader::FromFile(&file).unwrap());
+        cache
+            .GetOrCreate(&descriptor, &[], FORMAT, || Create(&renderer, &descriptor))
+            .unwrap();
+
+        assert_eq!(
+            cache.Reload(&cache.GetWatchedFiles(), |descriptor, layout| {
+                assert_eq!(cache.GetStats().pipelines, 1);
+                renderer.CreatePipelineWithLayout(descriptor, layout)
+            }),
+            1
+        );
+    }
+
+    #[test]
+    #[ignore = "needs an adapter"]
+    fn LayoutsWithEqualEntriesShareThePipeline() {
+        let renderer = Renderer::NewForTests();
+
+        let cache = PipelineCache::New();
+        let descriptor = PipelineDescriptor::New(Shader::FromWgsl(SHADER));
+
+        for _ in 0..2 {
+            let layout = BindGroupLayout::New(&renderer.device, "Layout", &[Uniform(0)]);
+
+            cache
+                .GetOrCreate(&descriptor, &[&layout], FORMAT, || {
+                    Create(&renderer, &descriptor)
+                })
+                .unwrap();
+        }
+
+        assert_eq!(
+            cache.GetStats(),
+            PipelineCacheStats {
+                hits: 1,
+                misses: 1,
+                pipelines: 1,
+            }
+        );
+    }
+}
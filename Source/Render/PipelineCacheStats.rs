@@ -0,0 +1,12 @@
+/// Counters of a [`PipelineCache`](super::PipelineCache), used to spot pipeline churn.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PipelineCacheStats {
+    /// Requests answered with an already built pipeline.
+    pub hits: u64,
+
+    /// Requests that had to build a new pipeline.
+    pub misses: u64,
+
+    /// Number of pipelines currently cached.
+    pub pipelines: usize,
+}
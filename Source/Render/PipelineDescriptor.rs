@@ -0,0 +1,306 @@
+use super::{
+    BlendMode, CullMode, DepthStencilState, FrontFace, MultisampleState, PolygonMode,
+    PrimitiveTopology, RendererError, VertexAttribute, VertexBufferLayout, VertexLayoutError,
+};
+use crate::Shader::Shader;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Declarative description of a render pipeline.
+///
+/// Can be loaded from RON or JSON and turned into a `wgpu::RenderPipeline`
+/// with [`Renderer::CreatePipeline`](super::Renderer::CreatePipeline).
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineDescriptor {
+    pub label: Cow<'static, str>,
+    pub shader: Shader,
+    /// Separate fragment shader, needed for single stage sources like GLSL.
+    /// Uses `shader` when `None`.
+    pub fragment_shader: Option<Shader>,
+    pub vertex_entry_point: Cow<'static, str>,
+    /// Fragment entry point, `None` for depth only pipelines.
+    pub fragment_entry_point: Option<Cow<'static, str>>,
+    pub vertex_layouts: Vec<VertexBufferLayout>,
+    pub topology: PrimitiveTopology,
+    pub front_face: FrontFace,
+    pub cull_mode: CullMode,
+    pub polygon_mode: PolygonMode,
+    pub blend: BlendMode,
+    pub depth_stencil: Option<DepthStencilState>,
+    pub multisample: MultisampleState,
+}
+
+impl PipelineDescriptor {
+    pub fn New(shader: Shader) -> Self {
+        Self {
+            shader,
+            ..Default::default()
+        }
+    }
+
+    /// Files the shaders of the pipeline are built from, watched for hot reload.
+    pub fn GetShaderFiles(&self) -> Vec<PathBuf> {
+        let mut files = self.shader.GetFiles();
+
+        if let Some(shader) = &self.fragment_shader {
+            files.extend(shader.GetFiles());
+        }
+
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    /// Reads every shader loaded from a file again.
+    pub fn ReloadShaders(&mut self) -> Result<(), RendererError> {
+        self.shader.Reload()?;
+
+        if let Some(shader) = &mut self.fragment_shader {
+            shader.Reload()?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the vertex layouts against each other and the inputs of the vertex entry point.
+    ///
+    /// Returns every problem found as [`RendererError::VertexLayout`] instead of stopping at the first.
+    pub fn ValidateVertexLayouts(&self) -> Result<(), RendererError> {
+        let inputs = self.shader.GetVertexInputs(&self.vertex_entry_point)?;
+
+        let mut errors = Vec::new();
+        let mut attributes = HashMap::<u32, &VertexAttribute>::new();
+
+        for layout in &self.vertex_layouts {
+            errors.extend(layout.Validate());
+
+            for attribute in &layout.attributes {
+                match attributes.entry(attribute.shader_location) {
+                    Entry::Occupied(first) => {
+                        errors.push(VertexLayoutError::DuplicateLocation {
+                            location: attribute.shader_location,
+                            first: first.get().label.clone(),
+                            second: attribute.label.clone(),
+                        });
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(attribute);
+                    }
+                }
+            }
+        }
+
+        for input in inputs {
+            match attributes.get(&input.location) {
+                Some(attribute) if !attribute.format.IsCompatibleWith(input.format) => {
+                    errors.push(VertexLayoutError::IncompatibleFormat {
+                        attribute: attribute.label.clone(),
+                        location: input.location,
+                        format: attribute.format,
+                        expected: input.format,
+                    });
+                }
+                Some(_) => {}
+                None => errors.push(VertexLayoutError::MissingLocation {
+                    input: input.name,
+                    location: input.location,
+                }),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(RendererError::VertexLayout(errors))
+        }
+    }
+}
+
+impl Default for PipelineDescriptor {
+    fn default() -> Self {
+        Self {
+            label: "".into(),
+            shader: Shader::FromWgsl(""),
+            fragment_shader: None,
+            vertex_entry_point: "vs_main".into(),
+            fragment_entry_point: Some("fs_main".into()),
+            vertex_layouts: Vec::new(),
+            topology: PrimitiveTopology::default(),
+            front_face: FrontFace::default(),
+            cull_mode: CullMode::default(),
+            polygon_mode: PolygonMode::default(),
+            blend: BlendMode::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Render::{InstanceRaw, ModelVertex, StepMode, Vertex, VertexFormat};
+
+    const SHADER: &str = "
+        struct VertexInput {
+            @location(0) position: vec3<f32>,
+            @location(1) id: u32,
+        };
+
+        @vertex
+        fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+            return vec4<f32>(in.position, f32(in.id));
+        }
+    ";
+
+    fn Attribute(label: &'static str, format: VertexFormat, location: u32) -> VertexAttribute {
+        VertexAttribute {
+            label: label.into(),
+            format,
+            offset: 0,
+            shader_location: location,
+        }
+    }
+
+    fn Descriptor(attributes: Vec<Vec<VertexAttribute>>) -> PipelineDescriptor {
+        PipelineDescriptor {
+            vertex_layouts: attributes
+                .into_iter()
+                .map(|attributes| VertexBufferLayout {
+                    label: "Layout".into(),
+                    stride: 16,
+                    step_mode: StepMode::Vertex,
+                    attributes,
+                })
+                .collect(),
+            ..PipelineDescriptor::New(Shader::FromWgsl(SHADER))
+        }
+    }
+
+    fn Errors(descriptor: &PipelineDescriptor) -> Vec<VertexLayoutError> {
+        match descriptor.ValidateVertexLayouts() {
+            Ok(()) => Vec::new(),
+            Err(RendererError::VertexLayout(errors)) => errors,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    #[test]
+    fn MatchingLayoutsPass() {
+        let descriptor = Descriptor(vec![
+            vec![Attribute("Position", VertexFormat::Float32x3, 0)],
+            vec![Attribute("Id", VertexFormat::UInt32, 1)],
+        ]);
+
+        assert!(Errors(&descriptor).is_empty());
+    }
+
+    #[test]
+    fn DuplicateLocation() {
+        let mut descriptor = Descriptor(vec![
+            vec![Attribute("Position", VertexFormat::Float32x3, 0)],
+            vec![
+                Attribute("Id", VertexFormat::UInt32, 1),
+                Attribute("Other", VertexFormat::Float32x3, 0),
+            ],
+        ]);
+        descriptor.vertex_layouts[1].attributes[1].offset = 4;
+
+        assert_eq!(
+            Errors(&descriptor),
+            vec![VertexLayoutError::DuplicateLocation {
+                location: 0,
+                first: "Position".into(),
+                second: "Other".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn LocationMissingFromLayouts() {
+        let descriptor = Descriptor(vec![vec![Attribute(
+            "Position",
+            VertexFormat::Float32x3,
+            0,
+        )]]);
+
+        assert_eq!(
+            Errors(&descriptor),
+            vec![VertexLayoutError::MissingLocation {
+                input: "id".into(),
+                location: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn ScalarKindMismatch() {
+        let descriptor = Descriptor(vec![
+            vec![Attribute("Position", VertexFormat::Float32x3, 0)],
+            vec![Attribute("Id", VertexFormat::Float32, 1)],
+        ]);
+
+        assert_eq!(
+            Errors(&descriptor),
+            vec![VertexLayoutError::IncompatibleFormat {
+                attribute: "Id".into(),
+                location: 1,
+                format: VertexFormat::Float32,
+                expected: VertexFormat::UInt32,
+            }]
+        );
+    }
+
+    #[test]
+    fn FewerComponentsAreCompatible() {
+        let descriptor = Descriptor(vec![
+            vec![Attribute("Position", VertexFormat::Float32x2, 0)],
+            vec![Attribute("Id", VertexFormat::UInt16x2, 1)],
+        ]);
+
+        assert!(Errors(&descriptor).is_empty());
+    }
+
+    #[test]
+    fn LayoutErrorsAreReported() {
+        let mut descriptor = Descriptor(vec![
+            vec![Attribute("Position", VertexFormat::Float32x3, 0)],
+            vec![Attribute("Id", VertexFormat::UInt32, 1)],
+        ]);
+        descriptor.vertex_layouts[1].attributes[0].offset = 14;
+
+        assert_eq!(
+            Errors(&descriptor),
+            vec![
+                VertexLayoutError::Misaligned {
+                    layout: "Layout".into(),
+                    attribute: "Id".into(),
+                    offset: 14,
+                },
+                VertexLayoutError::OutOfBounds {
+                    layout: "Layout".into(),
+                    attribute: "Id".into(),
+                    offset: 14,
+                    size: 4,
+                    stride: 16,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn CubesLayoutsMatchShader() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("Shaders/Cubes.wgsl");
+
+        let descriptor = PipelineDescriptor {
+            vertex_layouts: vec![ModelVertex::GetLayout(), InstanceRaw::GetLayout()],
+            ..PipelineDescriptor::New(Shader::FromFile(path).unwrap())
+        };
+
+        assert!(Errors(&descriptor).is_empty());
+    }
+}
@@ -0,0 +1,27 @@
+use std::sync::{Arc, RwLock};
+
+/// Shared reference to a pipeline built through [`Renderer::GetPipeline`](super::Renderer::GetPipeline).
+///
+/// The pipeline behind the handle is replaced when its shaders are hot reloaded,
+/// fetch it with [`PipelineHandle::Get`] every frame instead of keeping it around.
+#[derive(Clone, Debug)]
+pub struct PipelineHandle {
+    pipeline: Arc<RwLock<Arc<wgpu::RenderPipeline>>>,
+}
+
+impl PipelineHandle {
+    pub(crate) fn New(pipeline: wgpu::RenderPipeline) -> Self {
+        Self {
+            pipeline: Arc::new(RwLock::new(Arc::new(pipeline))),
+        }
+    }
+
+    /// Returns the current pipeline.
+    pub fn Get(&self) -> Arc<wgpu::RenderPipeline> {
+        self.pipeline.read().unwrap().clone()
+    }
+
+    pub(crate) fn Set(&self, pipeline: wgpu::RenderPipeline) {
+        *self.pipeline.write().unwrap() = Arc::new(pipeline);
+    }
+}
@@ -18,3 +18,24 @@ impl Default for PolygonMode {
         Self::Fill
     }
 }
+
+impl PolygonMode {
+    /// Returns the device features needed to rasterize with this mode.
+    pub fn GetRequiredFeatures(&self) -> wgpu::Features {
+        match *self {
+            Self::Fill => wgpu::Features::empty(),
+            Self::Line => wgpu::Features::POLYGON_MODE_LINE,
+            Self::Point => wgpu::Features::POLYGON_MODE_POINT,
+        }
+    }
+}
+
+impl From<PolygonMode> for wgpu::PolygonMode {
+    fn from(polygon_mode: PolygonMode) -> Self {
+        match polygon_mode {
+            PolygonMode::Fill => wgpu::PolygonMode::Fill,
+            PolygonMode::Line => wgpu::PolygonMode::Line,
+            PolygonMode::Point => wgpu::PolygonMode::Point,
+        }
+    }
+}
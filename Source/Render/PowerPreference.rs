@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum PowerPreference {
+    /// Prefers an integrated or otherwise power efficient adapter.
+    LowPower = 0,
+
+    /// Prefers a discrete or otherwise high performance adapter.
+    HighPerformance = 1,
+}
+
+impl Default for PowerPreference {
+    fn default() -> Self {
+        Self::HighPerformance
+    }
+}
+
+impl From<PowerPreference> for wgpu::PowerPreference {
+    fn from(power_preference: PowerPreference) -> Self {
+        match power_preference {
+            PowerPreference::LowPower => wgpu::PowerPreference::LowPower,
+            PowerPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+        }
+    }
+}
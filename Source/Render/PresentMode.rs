@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum PresentMode {
+    /// Waits for the vertical blank, frames are never dropped or torn (vsync on).
+    Fifo = 0,
+
+    /// Waits for the vertical blank but replaces queued frames, lowering latency.
+    Mailbox = 1,
+
+    /// Presents immediately, may tear (vsync off).
+    Immediate = 2,
+}
+
+impl Default for PresentMode {
+    fn default() -> Self {
+        Self::Fifo
+    }
+}
+
+impl From<PresentMode> for wgpu::PresentMode {
+    fn from(present_mode: PresentMode) -> Self {
+        match present_mode {
+            PresentMode::Fifo => wgpu::PresentMode::Fifo,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ConvertsToWgpu() {
+        assert_eq!(
+            wgpu::PresentMode::from(PresentMode::default()),
+            wgpu::PresentMode::Fifo
+        );
+        assert_eq!(
+            wgpu::PresentMode::from(PresentMode::Mailbox),
+            wgpu::PresentMode::Mailbox
+        );
+        assert_eq!(
+            wgpu::PresentMode::from(PresentMode::Immediate),
+            wgpu::PresentMode::Immediate
+        );
+    }
+}
@@ -0,0 +1,629 @@
+use super::MeshData;
+use cgmath::{InnerSpace, Vector3};
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+
+/// Generators for common shapes, centered on the origin with Y up.
+///
+/// Triangles wind counter-clockwise seen from outside and the vertices carry
+/// normals, texture coordinates and tangents. Upload the result with
+/// [`MeshData::Upload`] or wrap it in a model with [`Model::FromMeshData`](super::Model::FromMeshData).
+pub struct Primitive;
+
+impl Primitive {
+    /// Plane on XZ facing up, split into `subdivisions` quads per side.
+    pub fn Plane(width: f32, depth: f32, subdivisions: u32) -> MeshData {
+        let mut data = MeshData::New();
+
+        AddFace(
+            &mut data,
+            Vector3::new(-width / 2.0, 0.0, depth / 2.0),
+            Vector3::unit_x() * width,
+            -Vector3::unit_z() * depth,
+            subdivisions,
+        );
+
+        Finish(data)
+    }
+
+    /// Cube with its own vertices per face, so edges stay sharp.
+    pub fn Cube(size: f32, subdivisions: u32) -> MeshData {
+        let mut data = MeshData::New();
+        let half = size / 2.0;
+
+        // Normal, right and up axis of each face, right x up = normal
+        let faces = [
+            (Vector3::unit_x(), -Vector3::unit_z(), Vector3::unit_y()),
+            (-Vector3::unit_x(), Vector3::unit_z(), Vector3::unit_y()),
+            (Vector3::unit_y(), Vector3::unit_x(), -Vector3::unit_z()),
+            (-Vector3::unit_y(), Vector3::unit_x(), Vector3::unit_z()),
+            (Vector3::unit_z(), Vector3::unit_x(), Vector3::unit_y()),
+            (-Vector3::unit_z(), -Vector3::unit_x(), Vector3::unit_y()),
+        ];
+
+        for (normal, right, up) in faces {
+            let origin = (normal - right - up) * half;
+            AddFace(&mut data, origin, right * size, up * size, subdivisions);
+        }
+
+        Finish(data)
+    }
+
+    /// Sphere made of `segments` slices around Y and `rings` stacks from pole to pole.
+    pub fn UvSphere(radius: f32, segments: u32, rings: u32) -> MeshData {
+        let rings = rings.max(2);
+
+        let profile = (0..=rings)
+            .map(|ring| {
+                let phi = PI * ring as f32 / rings as f32;
+                let (sin, cos) = phi.sin_cos();
+
+                ProfilePoint {
+                    radius: radius * sin,
+                    height: radius * cos,
+                    normal: [sin, cos],
+                    v: ring as f32 / rings as f32,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut data = MeshData::New();
+        AddLathe(&mut data, &profile, segments);
+
+        Finish(data)
+    }
+
+    /// Sphere from a subdivided icosahedron, the triangles are evenly sized unlike [`Primitive::UvSphere`].
+    pub fn Icosphere(radius: f32, subdivisions: u32) -> MeshData {
+        let t = (1.0 + 5f32.sqrt()) / 2.0;
+
+        let mut positions = [
+            [-1.0, t, 0.0],
+            [1.0, t, 0.0],
+            [-1.0, -t, 0.0],
+            [1.0, -t, 0.0],
+            [0.0, -1.0, t],
+            [0.0, 1.0, t],
+            [0.0, -1.0, -t],
+            [0.0, 1.0, -t],
+            [t, 0.0, -1.0],
+            [t, 0.0, 1.0],
+            [-t, 0.0, -1.0],
+            [-t, 0.0, 1.0],
+        ]
+        .map(|p| Vector3::from(p).normalize())
+        .to_vec();
+
+        #[rustfmt::skip]
+        let mut triangles = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            let mut midpoints = HashMap::<(u32, u32), u32>::new();
+            let mut midpoint = |a: u32, b: u32| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let position = (positions[a as usize] + positions[b as usize]).normalize();
+                    positions.push(position);
+                    positions.len() as u32 - 1
+                })
+            };
+
+            triangles = triangles
+                .into_iter()
+                .flat_map(|[a, b, c]| {
+                    let ab = midpoint(a, b);
+                    let bc = midpoint(b, c);
+                    let ca = midpoint(c, a);
+
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        let mut data = MeshData::New();
+
+        for &normal in &positions {
+            data.positions.push((normal * radius).into());
+            data.normals.push(normal.into());
+            data.tex_coords.push(SphereTexCoords(normal));
+        }
+
+        for triangle in triangles {
+            let tex_coords = triangle.map(|i| data.tex_coords[i as usize]);
+            let wraps = tex_coords.iter().any(|uv| uv[0] > 0.75)
+                && tex_coords.iter().any(|uv| uv[0] < 0.25);
+
+            for (i, [u, v]) in triangle.into_iter().zip(tex_coords) {
+                // Triangles across the seam get copies of their left vertices shifted by one
+                let index = if wraps && u < 0.25 {
+                    data.positions.push(data.positions[i as usize]);
+                    data.normals.push(data.normals[i as usize]);
+                    data.tex_coords.push([u + 1.0, v]);
+                    data.positions.len() as u32 - 1
+                } else {
+                    i
+                };
+
+                data.indices.push(index);
+            }
+        }
+
+        Finish(data)
+    }
+
+    /// Cylinder along Y with caps, `height_segments` stacks along the side.
+    pub fn Cylinder(radius: f32, height: f32, segments: u32, height_segments: u32) -> MeshData {
+        let height_segments = height_segments.max(1);
+
+        let profile = (0..=height_segments)
+            .map(|stack| {
+                let v = stack as f32 / height_segments as f32;
+
+                ProfilePoint {
+                    radius,
+                    height: height / 2.0 - height * v,
+                    normal: [1.0, 0.0],
+                    v,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut data = MeshData::New();
+        AddLathe(&mut data, &profile, segments);
+        AddDisc(&mut data, radius, height / 2.0, segments, true);
+        AddDisc(&mut data, radius, -height / 2.0, segments, false);
+
+        Finish(data)
+    }
+
+    /// Cone along Y with its apex at the top and a capped base.
+    pub fn Cone(radius: f32, height: f32, segments: u32) -> MeshData {
+        let slope = Vector3::new(height, radius, 0.0).normalize();
+        let normal = [slope.x, slope.y];
+
+        let profile = [
+            ProfilePoint {
+                radius: 0.0,
+                height: height / 2.0,
+                normal,
+                v: 0.0,
+            },
+            ProfilePoint {
+                radius,
+                height: -height / 2.0,
+                normal,
+                v: 1.0,
+            },
+        ];
+
+        let mut data = MeshData::New();
+        AddLathe(&mut data, &profile, segments);
+        AddDisc(&mut data, radius, -height / 2.0, segments, false);
+
+        Finish(data)
+    }
+
+    /// Cylinder of `height` capped with hemispheres, `rings` stacks per hemisphere.
+    pub fn Capsule(radius: f32, height: f32, segments: u32, rings: u32) -> MeshData {
+        let rings = rings.max(1);
+        let length = PI * radius + height;
+
+        let mut profile = Vec::new();
+
+        for (center, first_ring) in [(height / 2.0, 0), (-height / 2.0, rings)] {
+            for ring in first_ring..=first_ring + rings {
+                let phi = PI / 2.0 * ring as f32 / rings as f32;
+                let (sin, cos) = phi.sin_cos();
+
+                // Arc length from the top pole, the cylinder adds its height halfway
+                let distance = radius * phi + if first_ring > 0 { height } else { 0.0 };
+
+                profile.push(ProfilePoint {
+                    radius: radius * sin,
+                    height: center + radius * cos,
+                    normal: [sin, cos],
+                    v: distance / length,
+                });
+            }
+        }
+
+        let mut data = MeshData::New();
+        AddLathe(&mut data, &profile, segments);
+
+        Finish(data)
+    }
+
+    /// Torus around Y, `major_radius` to the center of the tube of `minor_radius`.
+    pub fn Torus(
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: u32,
+        minor_segments: u32,
+    ) -> MeshData {
+        let minor_segments = minor_segments.max(3);
+
+        // Starts at the outer edge and runs down the outside, like the other profiles
+        let profile = (0..=minor_segments)
+            .map(|segment| {
+                let v = segment as f32 / minor_segments as f32;
+                let (sin, cos) = (-TAU * v).sin_cos();
+
+                ProfilePoint {
+                    radius: major_radius + minor_radius * cos,
+                    height: minor_radius * sin,
+                    normal: [cos, sin],
+                    v,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut data = MeshData::New();
+        AddLathe(&mut data, &profile, major_segments);
+
+        Finish(data)
+    }
+
+    /// Lines on XZ with `divisions` cells per side, for debug views.
+    ///
+    /// The indices form a line list, draw it with [`PrimitiveTopology::LineList`](super::PrimitiveTopology::LineList).
+    pub fn Grid(size: f32, divisions: u32) -> MeshData {
+        let divisions = divisions.max(1);
+        let half = size / 2.0;
+
+        let mut data = MeshData::New();
+
+        for i in 0..=divisions {
+            let t = i as f32 / divisions as f32;
+            let offset = -half + size * t;
+
+            let lines = [
+                (
+                    [offset, 0.0, -half],
+                    [offset, 0.0, half],
+                    [[t, 0.0], [t, 1.0]],
+                ),
+                (
+                    [-half, 0.0, offset],
+                    [half, 0.0, offset],
+                    [[0.0, t], [1.0, t]],
+                ),
+            ];
+
+            for (start, end, tex_coords) in lines {
+                data.indices.push(data.positions.len() as u32);
+                data.indices.push(data.positions.len() as u32 + 1);
+                data.positions.extend([start, end]);
+                data.normals.extend([[0.0, 1.0, 0.0]; 2]);
+                data.tex_coords.extend(tex_coords);
+                data.tangents.extend([[1.0, 0.0, 0.0, 1.0]; 2]);
+            }
+        }
+
+        data
+    }
+}
+
+/// Point of a profile revolved by [`AddLathe`].
+struct ProfilePoint {
+    radius: f32,
+    height: f32,
+    /// Radial and vertical component of the normal.
+    normal: [f32; 2],
+    v: f32,
+}
+
+/// Adds a quad grid spanning `right` and `up` from `origin`, facing `right x up`.
+fn AddFace(
+    data: &mut MeshData,
+    origin: Vector3<f32>,
+    right: Vector3<f32>,
+    up: Vector3<f32>,
+    subdivisions: u32,
+) {
+    let subdivisions = subdivisions.max(1);
+    let columns = subdivisions + 1;
+    let first = data.positions.len() as u32;
+    let normal = right.cross(up).normalize();
+
+    for row in 0..=subdivisions {
+        for column in 0..=subdivisions {
+            let u = column as f32 / subdivisions as f32;
+            let v = row as f32 / subdivisions as f32;
+
+            data.positions.push((origin + right * u + up * v).into());
+            data.normals.push(normal.into());
+            data.tex_coords.push([u, 1.0 - v]);
+        }
+    }
+
+    for row in 0..subdivisions {
+        for column in 0..subdivisions {
+            let a = first + row * columns + column;
+            let b = a + 1;
+            let c = b + columns;
+            let d = a + columns;
+
+            data.indices.extend([a, b, c, a, c, d]);
+        }
+    }
+}
+
+/// Revolves a profile running downwards on the outside around the Y axis.
+fn AddLathe(data: &mut MeshData, profile: &[ProfilePoint], segments: u32) {
+    let segments = segments.max(3);
+    let count = profile.len() as u32;
+    let first = data.positions.len() as u32;
+
+    // The seam column is duplicated so texture coordinates can reach one
+    for segment in 0..=segments {
+        let u = segment as f32 / segments as f32;
+        let (sin, cos) = (TAU * u).sin_cos();
+
+        for point in profile {
+            data.positions
+                .push([point.radius * sin, point.height, point.radius * cos]);
+            data.normals.push([
+                point.normal[0] * sin,
+                point.normal[1],
+                point.normal[0] * cos,
+            ]);
+            data.tex_coords.push([u, point.v]);
+        }
+    }
+
+    for segment in 0..segments {
+        for (j, pair) in profile.windows(2).enumerate() {
+            let a = first + segment * count + j as u32;
+            let b = a + count;
+            let c = b + 1;
+            let d = a + 1;
+
+            // Points on the axis collapse one of the triangles
+            if pair[1].radius > 0.0 {
+                data.indices.extend([a, d, c]);
+            }
+
+            if pair[0].radius > 0.0 {
+                data.indices.extend([a, c, b]);
+            }
+        }
+    }
+}
+
+/// Adds a disc at `height`, facing up or down.
+fn AddDisc(data: &mut MeshData, radius: f32, height: f32, segments: u32, is_facing_up: bool) {
+    let segments = segments.max(3);
+    let normal = if is_facing_up { 1.0 } else { -1.0 };
+    let center = data.positions.len() as u32;
+
+    data.positions.push([0.0, height, 0.0]);
+    data.normals.push([0.0, normal, 0.0]);
+    data.tex_coords.push([0.5, 0.5]);
+
+    for segment in 0..segments {
+        let (sin, cos) = (TAU * segment as f32 / segments as f32).sin_cos();
+
+        data.positions.push([radius * sin, height, radius * cos]);
+        data.normals.push([0.0, normal, 0.0]);
+        data.tex_coords
+            .push([0.5 + sin / 2.0, 0.5 + cos * normal / 2.0]);
+    }
+
+    for segment in 0..segments {
+        let a = center + 1 + segment;
+        let b = center + 1 + (segment + 1) % segments;
+
+        if is_facing_up {
+            data.indices.extend([center, a, b]);
+        } else {
+            data.indices.extend([center, b, a]);
+        }
+    }
+}
+
+/// Equirectangular texture coordinates of a direction, matching [`Primitive::UvSphere`].
+fn SphereTexCoords(normal: Vector3<f32>) -> [f32; 2] {
+    let u = (normal.x.atan2(normal.z) / TAU).rem_euclid(1.0);
+    let v = normal.y.clamp(-1.0, 1.0).acos() / PI;
+
+    [u, v]
+}
+
+fn Finish(mut data: MeshData) -> MeshData {
+    // Every generator provides the normals and texture coordinates tangents need
+    let has_tangents = data.ComputeTangents();
+    debug_assert!(has_tangents, "failed to generate tangents");
+
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn Shapes() -> Vec<(&'static str, MeshData)> {
+        vec![
+            ("Plane", Primitive::Plane(2.0, 1.0, 3)),
+            ("Cube", Primitive::Cube(1.0, 2)),
+            ("UvSphere", Primitive::UvSphere(1.0, 16, 8)),
+            ("Icosphere", Primitive::Icosphere(1.0, 2)),
+            ("Cylinder", Primitive::Cylinder(0.5, 2.0, 12, 2)),
+            ("Cone", Primitive::Cone(0.5, 1.0, 12)),
+            ("Capsule", Primitive::Capsule(0.5, 1.0, 12, 4)),
+            ("Torus", Primitive::Torus(1.0, 0.25, 16, 8)),
+        ]
+    }
+
+    fn Position(data: &MeshData, index: u32) -> Vector3<f32> {
+        Vector3::from(data.positions[index as usize])
+    }
+
+    #[test]
+    fn IndicesAreInRange() {
+        for (name, data) in Shapes() {
+            assert!(!data.indices.is_empty(), "{}", name);
+            assert_eq!(data.indices.len() % 3, 0, "{}", name);
+            assert!(
+                data.indices
+                    .iter()
+                    .all(|&i| (i as usize) < data.GetVertexCount()),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn AttributesAreComplete() {
+        for (name, data) in Shapes() {
+            let count = data.GetVertexCount();
+
+            assert_eq!(data.normals.len(), count, "{}", name);
+            assert_eq!(data.tex_coords.len(), count, "{}", name);
+            assert_eq!(data.tangents.len(), count, "{}", name);
+        }
+    }
+
+    #[test]
+    fn NormalsAndTangentsAreUnitLength() {
+        for (name, data) in Shapes() {
+            for (normal, tangent) in data.normals.iter().zip(&data.tangents) {
+                let normal = Vector3::from(*normal);
+                let direction = Vector3::new(tangent[0], tangent[1], tangent[2]);
+
+                assert!(
+                    (normal.magnitude() - 1.0).abs() < 1e-4,
+                    "{}: {:?}",
+                    name,
+                    normal
+                );
+                assert!(
+                    (direction.magnitude() - 1.0).abs() < 1e-3,
+                    "{}: {:?}",
+                    name,
+                    tangent
+                );
+                assert!(
+                    direction.dot(normal).abs() < 1e-3,
+                    "{}: {:?}",
+                    name,
+                    tangent
+                );
+                assert_eq!(tangent[3].abs(), 1.0, "{}: {:?}", name, tangent);
+            }
+        }
+    }
+
+    #[test]
+    fn TrianglesWindCounterClockwiseFromOutside() {
+        for (name, data) in Shapes() {
+            for triangle in data.indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| Position(&data, triangle[i]));
+                let normal = triangle
+                    .iter()
+                    .map(|&i| Vector3::from(data.normals[i as usize]))
+                    .sum::<Vector3<f32>>();
+
+                assert!(
+                    (b - a).cross(c - a).dot(normal) > 0.0,
+                    "{}: {:?}",
+                    name,
+                    triangle
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ClosedShapesAreWatertight() {
+        for (name, data) in Shapes() {
+            if !matches!(name, "Cube" | "UvSphere" | "Icosphere") {
+                continue;
+            }
+
+            // Vertices split for normals or texture coordinates share their position
+            let mut welded = MeshData {
+                positions: data.positions.clone(),
+                indices: data.indices.clone(),
+                ..Default::default()
+            };
+            welded.Weld(1e-4);
+
+            // Each directed edge appears once, its reverse belongs to the neighbour
+            let mut edges = HashMap::<(u32, u32), u32>::new();
+            for triangle in welded.indices.chunks_exact(3) {
+                for i in 0..3 {
+                    *edges
+                        .entry((triangle[i], triangle[(i + 1) % 3]))
+                        .or_default() += 1;
+                }
+            }
+
+            for (&(a, b), &count) in &edges {
+                assert_eq!(count, 1, "{}: edge {} {}", name, a, b);
+                assert_eq!(edges.get(&(b, a)), Some(&1), "{}: edge {} {}", name, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn TexCoordsStayInRange() {
+        for (name, data) in Shapes() {
+            if name == "Icosphere" {
+                continue;
+            }
+
+            for [u, v] in &data.tex_coords {
+                assert!((0.0..=1.0).contains(u), "{}: {}", name, u);
+                assert!((0.0..=1.0).contains(v), "{}: {}", name, v);
+            }
+        }
+    }
+
+    #[test]
+    fn IcosphereSeamUsesShiftedCopies() {
+        let data = Primitive::Icosphere(1.0, 2);
+
+        for (position, [u, v]) in data.positions.iter().zip(&data.tex_coords) {
+            let expected = SphereTexCoords(Vector3::from(*position));
+
+            // Copies on the right of the seam continue past one
+            assert!(
+                (u - expected[0]).abs() < 1e-5 || (u - 1.0 - expected[0]).abs() < 1e-5,
+                "{} {:?}",
+                u,
+                expected
+            );
+            assert!((0.0..=1.0).contains(v));
+        }
+
+        // No triangle spans the whole texture, except the fans around the poles
+        for triangle in data.indices.chunks_exact(3) {
+            let is_pole = triangle
+                .iter()
+                .any(|&i| data.positions[i as usize][1].abs() > 0.9999);
+            let us = [0, 1, 2].map(|i| data.tex_coords[triangle[i] as usize][0]);
+            let span = us.iter().cloned().fold(f32::MIN, f32::max)
+                - us.iter().cloned().fold(f32::MAX, f32::min);
+
+            assert!(is_pole || span < 0.5, "{:?}", us);
+        }
+    }
+
+    #[test]
+    fn GridIsLineList() {
+        let data = Primitive::Grid(2.0, 4);
+
+        // Five lines along each axis
+        assert_eq!(data.indices.len(), 2 * 2 * 5);
+        assert!(data
+            .indices
+            .iter()
+            .all(|&i| (i as usize) < data.GetVertexCount()));
+        assert!(data.positions.iter().all(|p| p[1] == 0.0));
+    }
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum PrimitiveTopology {
+    /// Every vertex is drawn as a point.
+    PointList = 0,
+
+    /// Every two vertices form a line.
+    LineList = 1,
+
+    /// Every vertex after the first continues the line from the previous one.
+    LineStrip = 2,
+
+    /// Every three vertices form a triangle.
+    TriangleList = 3,
+
+    /// Every vertex after the first two forms a triangle with the previous two.
+    TriangleStrip = 4,
+}
+
+impl Default for PrimitiveTopology {
+    fn default() -> Self {
+        Self::TriangleList
+    }
+}
+
+impl From<PrimitiveTopology> for wgpu::PrimitiveTopology {
+    fn from(topology: PrimitiveTopology) -> Self {
+        match topology {
+            PrimitiveTopology::PointList => wgpu::PrimitiveTopology::PointList,
+            PrimitiveTopology::LineList => wgpu::PrimitiveTopology::LineList,
+            PrimitiveTopology::LineStrip => wgpu::PrimitiveTopology::LineStrip,
+            PrimitiveTopology::TriangleList => wgpu::PrimitiveTopology::TriangleList,
+            PrimitiveTopology::TriangleStrip => wgpu::PrimitiveTopology::TriangleStrip,
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use super::BindGroupLayout;
+
+/// Bind group layouts and pipeline layout built from a [`ShaderReflection`](crate::Shader::ShaderReflection).
+pub struct ReflectedLayouts {
+    /// One layout per group index, groups the shader skips get an empty layout.
+    pub bind_group_layouts: Vec<BindGroupLayout>,
+    pub pipeline_layout: wgpu::PipelineLayout,
+}
+
+impl ReflectedLayouts {
+    /// Borrows the bind group layouts in the form `Renderer::GetPipeline` takes.
+    pub fn GetBindGroupLayouts(&self) -> Vec<&BindGroupLayout> {
+        self.bind_group_layouts.iter().collect()
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::{bail, Result};
+use std::num::NonZeroU32;
+
+/// Offscreen color and depth attachments owned by a headless renderer.
+pub struct RenderTarget {
+    pub color_texture: wgpu::Texture,
+    pub color_view: wgpu::TextureView,
+    pub depth_texture: wgpu::Texture,
+    pub depth_view: wgpu::TextureView,
+    pub format: wgpu::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl RenderTarget {
+    pub const COLOR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    pub fn New(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        // Zero sized textures are invalid, clamp like a minimized window would be
+        let width = width.max(1);
+        let height = height.max(1);
+
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        let color_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("RENDER_TARGET_COLOR"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        });
+
+        let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("RENDER_TARGET_DEPTH"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        });
+
+        let color_view = color_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self {
+            color_texture,
+            color_view,
+            depth_texture,
+            depth_view,
+            format,
+            width,
+            height,
+        }
+    }
+
+    /// Copies the color attachment back to the CPU.
+    ///
+    /// Blocks until the GPU has finished all submitted work.
+    pub fn Capture(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<image::RgbaImage> {
+        let is_bgra = match self.format {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            format => bail!("Capturing {:?} render targets is not supported", format),
+        };
+
+        let unpadded_bytes_per_row = 4 * self.width;
+
+        // Buffer copies require rows aligned to 256 bytes
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("CAPTURE_BUFFER"),
+            size: (padded_bytes_per_row * self.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("CAPTURE_ENCODER"),
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                aspect: wgpu::TextureAspect::All,
+                texture: &self.color_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                    rows_per_image: NonZeroU32::new(self.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        device.poll(wgpu::Maintain::Wait);
+        pollster::block_on(mapping)?;
+
+        let mut pixels = StripRowPadding(
+            &slice.get_mapped_range(),
+            unpadded_bytes_per_row as usize,
+            padded_bytes_per_row as usize,
+        );
+
+        buffer.unmap();
+
+        if is_bgra {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        image::RgbaImage::from_raw(self.width, self.height, pixels)
+            .ok_or_else(|| anyhow::anyhow!("Captured frame has an unexpected size"))
+    }
+}
+
+/// Drops the alignment bytes at the end of every row of a buffer copy.
+fn StripRowPadding(
+    data: &[u8],
+    unpadded_bytes_per_row: usize,
+    padded_bytes_per_row: usize,
+) -> Vec<u8> {
+    data.chunks(padded_bytes_per_row)
+        .flat_map(|row| &row[..unpadded_bytes_per_row])
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn StripRowPaddingKeepsPixels() {
+        // 4 * 3 = 12 bytes per row, padded to the 256 byte copy alignment
+        let (width, height) = (3, 2);
+        let unpadded = 4 * width;
+        let padded = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+
+        let mut data = vec![0xAA; padded * height];
+        for row in 0..height {
+            for byte in 0..unpadded {
+                data[row * padded + byte] = (row * unpadded + byte) as u8;
+            }
+        }
+
+        let pixels = StripRowPadding(&data, unpadded, padded);
+
+        assert_eq!(pixels.len(), unpadded * height);
+        assert_eq!(pixels, (0..(unpadded * height) as u8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn StripRowPaddingWithoutPadding() {
+        let data = (0..=255).collect::<Vec<u8>>();
+        assert_eq!(StripRowPadding(&data, 64, 64), data);
+    }
+}
@@ -1,72 +1,581 @@
-use super::{IndexBuffer, UniformBuffer, VertexBuffer};
-use crate::Shader::Shader;
-use anyhow::Result;
+use super::{
+    BindGroupLayout, Frame, IndexBuffer, IndexFormat, MipmapGeneratorCache, PipelineCache,
+    PipelineDescriptor, PipelineHandle, PolygonMode, PresentMode, ReflectedLayouts, RenderTarget,
+    RendererConfig, RendererError, UniformBuffer, VertexBuffer, WgpuVertexBufferLayout,
+};
+use crate::Color::RgbaColor;
+use crate::Shader::{Shader, ShaderReflection, ShaderSource, ShaderVariant, ShaderVariantKey};
+use crate::{FrameStats, WindowMode};
+use anyhow::{bail, Result};
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use wgpu::util::DeviceExt;
-use winit::window::Window;
+use winit::window::{Fullscreen, Window};
 
 pub struct Renderer {
-    pub surface: wgpu::Surface,
-    pub window: Window,
+    /// Window surface, `None` when running headless.
+    pub surface: Option<wgpu::Surface>,
+    pub window: Option<Window>,
+    /// Offscreen color and depth target, only present when running headless.
+    pub target: Option<RenderTarget>,
     pub config: wgpu::SurfaceConfiguration,
+    pub adapter: wgpu::Adapter,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
+    /// Features enabled on the device, required plus the supported optional ones.
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+    /// Frame timings, updated by the runtime before every update.
+    pub frame_stats: FrameStats,
+    /// Pipelines built through [`Renderer::GetPipeline`].
+    pub pipeline_cache: PipelineCache,
+    /// Generators used to fill texture mip chains on this device.
+    pub mipmap_generators: MipmapGeneratorCache,
+    instance: wgpu::Instance,
+    renderer_config: RendererConfig,
+    errors: Arc<Mutex<Vec<RendererError>>>,
+    /// Consecutive frames the surface was lost or outdated.
+    surface_failures: Cell<u32>,
 }
 
 impl Renderer {
-    pub async fn New(window: Window) -> Result<Self> {
+    /// Consecutive lost or outdated frames, each followed by a reconfigure, after which
+    /// the device is assumed lost.
+    pub const MAX_SURFACE_FAILURES: u32 = 3;
+
+    /// Creates a renderer drawing into `window`, presenting with `present_mode` when the
+    /// surface supports it and `Fifo` otherwise.
+    pub async fn New(
+        window: Window,
+        renderer_config: &RendererConfig,
+        present_mode: PresentMode,
+    ) -> Result<Self, RendererError> {
         let size = window.inner_size();
 
-        let instance = wgpu::Instance::new(wgpu::Backends::DX12);
+        let instance = wgpu::Instance::new(renderer_config.GetBackends());
         let surface = unsafe { instance.create_surface(&window) };
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .unwrap();
-
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: None,
-                    features: wgpu::Features::empty(),
-                    limits: wgpu::Limits::default(),
-                },
-                None,
-            )
-            .await
-            .unwrap();
+        let adapter = Self::SelectAdapter(&instance, renderer_config, Some(&surface)).await?;
+        let (device, queue) = Self::RequestDevice(&adapter, renderer_config).await?;
 
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface.get_preferred_format(&adapter).unwrap(),
+            format: surface
+                .get_preferred_format(&adapter)
+                .ok_or(RendererError::IncompatibleSurface)?,
             width: size.width,
             height: size.height,
-            present_mode: wgpu::PresentMode::Fifo,
+            present_mode: present_mode.into(),
         };
 
-        surface.configure(&device, &config);
+        Self::ConfigureSurface(&surface, &device, &config);
 
         Ok(Self {
-            surface,
-            window,
+            surface: Some(surface),
+            window: Some(window),
+            target: None,
             config,
+            frame_stats: FrameStats::default(),
+            pipeline_cache: PipelineCache::New(),
+            mipmap_generators: MipmapGeneratorCache::New(),
+            features: device.features(),
+            limits: device.limits(),
+            errors: Self::CaptureErrors(&device),
+            surface_failures: Cell::new(0),
+            adapter,
             device,
             queue,
+            instance,
+            renderer_config: renderer_config.clone(),
         })
     }
 
-    pub fn SubmitShader(&self, shader: &Shader) -> wgpu::ShaderModule {
-        let src = shader.source.WgslToString().unwrap();
+    /// Creates a renderer without a window, drawing into an offscreen target of the given size.
+    ///
+    /// Set `force_fallback_adapter` in the config to use the software adapter, which allows
+    /// running on machines without a GPU.
+    pub async fn NewHeadless(
+        width: u32,
+        height: u32,
+        renderer_config: &RendererConfig,
+    ) -> Result<Self, RendererError> {
+        let instance = wgpu::Instance::new(renderer_config.GetBackends());
 
-        self.device
+        let adapter = Self::SelectAdapter(&instance, renderer_config, None).await?;
+        let (device, queue) = Self::RequestDevice(&adapter, renderer_config).await?;
+
+        let target = RenderTarget::New(&device, width, height, RenderTarget::COLOR_FORMAT);
+
+        // Mirrors the surface configuration so states can read the target size and format
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: target.format,
+            width: target.width,
+            height: target.height,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+
+        Ok(Self {
+            surface: None,
+            window: None,
+            target: Some(target),
+            config,
+            frame_stats: FrameStats::default(),
+            pipeline_cache: PipelineCache::New(),
+            mipmap_generators: MipmapGeneratorCache::New(),
+            features: device.features(),
+            limits: device.limits(),
+            errors: Self::CaptureErrors(&device),
+            surface_failures: Cell::new(0),
+            adapter,
+            device,
+            queue,
+            instance,
+            renderer_config: renderer_config.clone(),
+        })
+    }
+
+    /// Replaces the device after it was lost.
+    ///
+    /// Every resource created from the old device is invalid afterwards and must be
+    /// created again, the runtime does this by initializing the state again.
+    pub async fn RecreateDevice(&mut self) -> Result<(), RendererError> {
+        let adapter =
+            Self::SelectAdapter(&self.instance, &self.renderer_config, self.surface.as_ref())
+                .await?;
+        let (device, queue) = Self::RequestDevice(&adapter, &self.renderer_config).await?;
+
+        if let Some(surface) = &self.surface {
+            self.config.format = surface
+                .get_preferred_format(&adapter)
+                .ok_or(RendererError::IncompatibleSurface)?;
+            Self::ConfigureSurface(surface, &device, &self.config);
+        }
+
+        if let Some(target) = &mut self.target {
+            *target = RenderTarget::New(&device, target.width, target.height, target.format);
+        }
+
+        // Pipelines belong to the old device
+        self.pipeline_cache.Clear();
+        self.mipmap_generators.Clear();
+
+        self.features = device.features();
+        self.limits = device.limits();
+        self.errors = Self::CaptureErrors(&device);
+        self.surface_failures.set(0);
+        self.adapter = adapter;
+        self.device = device;
+        self.queue = queue;
+
+        Ok(())
+    }
+
+    /// Collects uncaptured device errors instead of panicking, see [`Renderer::TakeErrors`].
+    fn CaptureErrors(device: &wgpu::Device) -> Arc<Mutex<Vec<RendererError>>> {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let sink = errors.clone();
+
+        device.on_uncaptured_error(move |error| {
+            log::error!("{}", error);
+            sink.lock().unwrap().push(error.into());
+        });
+
+        errors
+    }
+
+    /// Returns the device errors raised since the last call.
+    pub fn TakeErrors(&self) -> Vec<RendererError> {
+        std::mem::take(&mut *self.errors.lock().unwrap())
+    }
+
+    async fn SelectAdapter(
+        instance: &wgpu::Instance,
+        renderer_config: &RendererConfig,
+        surface: Option<&wgpu::Surface>,
+    ) -> Result<wgpu::Adapter, RendererError> {
+        let backends = renderer_config.GetBackends();
+
+        let adapter =
+            if renderer_config.adapter_name.is_some() || renderer_config.adapter_index.is_some() {
+                let mut adapters: Vec<_> = instance
+                    .enumerate_adapters(backends)
+                    .filter(|adapter| surface.is_none_or(|s| adapter.is_surface_supported(s)))
+                    .collect();
+
+                let infos: Vec<_> = adapters.iter().map(wgpu::Adapter::get_info).collect();
+
+                renderer_config
+                    .SelectAdapter(&infos)
+                    .map(|index| adapters.swap_remove(index))
+            } else {
+                instance
+                    .request_adapter(&wgpu::RequestAdapterOptions {
+                        power_preference: renderer_config.power_preference.into(),
+                        compatible_surface: surface,
+                        force_fallback_adapter: renderer_config.force_fallback_adapter,
+                    })
+                    .await
+            };
+
+        match adapter {
+            Some(adapter) => {
+                let info = adapter.get_info();
+                log::info!("Using adapter {} ({:?})", info.name, info.backend);
+                Ok(adapter)
+            }
+            None => {
+                let available = instance
+                    .enumerate_adapters(wgpu::Backends::all())
+                    .map(|adapter| {
+                        let info = adapter.get_info();
+                        format!("{} ({:?}, {:?})", info.name, info.backend, info.device_type)
+                    })
+                    .collect();
+
+                Err(RendererError::NoAdapter { available })
+            }
+        }
+    }
+
+    async fn RequestDevice(
+        adapter: &wgpu::Adapter,
+        renderer_config: &RendererConfig,
+    ) -> Result<(wgpu::Device, wgpu::Queue), RendererError> {
+        let name = adapter.get_info().name;
+
+        let missing = renderer_config.required_features - adapter.features();
+
+        if !missing.is_empty() {
+            return Err(RendererError::MissingFeatures(missing));
+        }
+
+        let features = renderer_config.GetFeatures(adapter);
+        let unavailable = renderer_config.optional_features - features;
+
+        if !unavailable.is_empty() {
+            log::warn!(
+                "Adapter {} does not support the optional features {:?}",
+                name,
+                unavailable
+            );
+        }
+
+        let limits = renderer_config.limits.GetLimits(adapter);
+        let unsupported = GetUnsupportedLimits(&limits, &adapter.limits());
+
+        if !unsupported.is_empty() {
+            return Err(RendererError::UnsupportedLimits(unsupported));
+        }
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: None,
+                    features,
+                    limits,
+                },
+                None,
+            )
+            .await?;
+
+        Ok((device, queue))
+    }
+
+    /// Checks whether all of the given features are enabled on the device.
+    pub fn HasFeatures(&self, features: wgpu::Features) -> bool {
+        self.features.contains(features)
+    }
+
+    pub fn SupportsPolygonMode(&self, polygon_mode: PolygonMode) -> bool {
+        self.HasFeatures(polygon_mode.GetRequiredFeatures())
+    }
+
+    pub fn IsHeadless(&self) -> bool {
+        self.surface.is_none()
+    }
+
+    /// Acquires the color target for the next frame.
+    ///
+    /// A surface that stays lost or outdated for [`Renderer::MAX_SURFACE_FAILURES`] frames
+    /// in a row is reported as [`RendererError::DeviceLost`].
+    pub fn GetCurrentFrame(&self) -> Result<Frame, RendererError> {
+        match (&self.surface, &self.target) {
+            (Some(surface), _) => match surface.get_current_texture() {
+                Ok(texture) => {
+                    self.surface_failures.set(0);
+                    Ok(Frame::FromSurface(texture))
+                }
+                Err(error) => {
+                    let failures = match error {
+                        wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated => {
+                            self.surface_failures.get() + 1
+                        }
+                        _ => 0,
+                    };
+
+                    self.surface_failures.set(failures);
+
+                    Err(EscalateSurfaceError(error, failures))
+                }
+            },
+            (None, Some(target)) => Ok(Frame::FromTexture(&target.color_texture)),
+            (None, None) => Err(wgpu::SurfaceError::Lost.into()),
+        }
+    }
+
+    pub fn SubmitShader(&self, shader: &Shader) -> Result<wgpu::ShaderModule, RendererError> {
+        // Every source goes through naga, which reports errors with their source location,
+        // so wgpu compiles exactly the module validation accepted
+        let source = wgpu::ShaderSource::SpirV(shader.CompileSpirv()?.into());
+
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let module = self
+            .device
             .create_shader_module(&wgpu::ShaderModuleDescriptor {
                 label: Some(&shader.label),
-                source: wgpu::ShaderSource::Wgsl(src.as_str().into()),
+                source,
+            });
+
+        match pollster::block_on(self.device.pop_error_scope()) {
+            // wgpu only sees the preprocessed source, prefer the naga error that points
+            // into the original files
+            Some(error) => {
+                Err(shader
+                    .Validate()
+                    .err()
+                    .unwrap_or_else(|| RendererError::ShaderCompilation {
+                        label: shader.label.to_string(),
+                        message: error.to_string(),
+                    }))
+            }
+            None => Ok(module),
+        }
+    }
+
+    /// Builds the bind group layouts and pipeline layout a reflected shader expects.
+    pub fn CreateReflectedLayouts(
+        &self,
+        label: &str,
+        reflection: &ShaderReflection,
+    ) -> ReflectedLayouts {
+        let bind_group_layouts = (0..reflection.GetGroupCount())
+            .map(|group| {
+                BindGroupLayout::New(
+                    &self.device,
+                    &format!("{} Group {}", label, group),
+                    &reflection.GetBindGroupLayoutEntries(group),
+                )
             })
+            .collect::<Vec<_>>();
+
+        let pipeline_layout = self.CreatePipelineLayout(
+            label,
+            &bind_group_layouts
+                .iter()
+                .map(BindGroupLayout::Get)
+                .collect::<Vec<_>>(),
+        );
+
+        ReflectedLayouts {
+            bind_group_layouts,
+            pipeline_layout,
+        }
+    }
+
+    /// Builds a render pipeline from its declarative description.
+    ///
+    /// The pipeline renders into the current surface or target format.
+    pub fn CreatePipeline(
+        &self,
+        descriptor: &PipelineDescriptor,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> Result<wgpu::RenderPipeline, RendererError> {
+        let layout = self.CreatePipelineLayout(&descriptor.label, bind_group_layouts);
+
+        self.CreatePipelineWithLayout(descriptor, &layout)
+    }
+
+    pub fn CreatePipelineLayout(
+        &self,
+        label: &str,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> wgpu::PipelineLayout {
+        self.device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            })
+    }
+
+    /// Same as [`Renderer::CreatePipeline`] with an existing pipeline layout.
+    pub fn CreatePipelineWithLayout(
+        &self,
+        descriptor: &PipelineDescriptor,
+        layout: &wgpu::PipelineLayout,
+    ) -> Result<wgpu::RenderPipeline, RendererError> {
+        let shader_module = self.SubmitShader(&descriptor.shader)?;
+
+        let fragment_module = match &descriptor.fragment_shader {
+            Some(shader) => Some(self.SubmitShader(shader)?),
+            None => None,
+        };
+
+        self.CreatePipelineWithModules(descriptor, layout, &shader_module, fragment_module.as_ref())
+    }
+
+    /// Same as [`Renderer::CreatePipelineWithLayout`] with already compiled modules, the
+    /// shaders of `descriptor` are only used to validate the vertex layouts.
+    ///
+    /// `fragment_module` replaces `shader_module` for the fragment stage when set.
+    pub fn CreatePipelineWithModules(
+        &self,
+        descriptor: &PipelineDescriptor,
+        layout: &wgpu::PipelineLayout,
+        shader_module: &wgpu::ShaderModule,
+        fragment_module: Option<&wgpu::ShaderModule>,
+    ) -> Result<wgpu::RenderPipeline, RendererError> {
+        if !self.SupportsPolygonMode(descriptor.polygon_mode) {
+            return Err(RendererError::MissingFeatures(
+                descriptor.polygon_mode.GetRequiredFeatures() - self.features,
+            ));
+        }
+
+        descriptor.ValidateVertexLayouts()?;
+
+        let vertex_layouts = descriptor
+            .vertex_layouts
+            .iter()
+            .map(WgpuVertexBufferLayout::from)
+            .collect::<Vec<_>>();
+
+        let buffers = vertex_layouts
+            .iter()
+            .map(WgpuVertexBufferLayout::AsWgpu)
+            .collect::<Vec<_>>();
+
+        let targets = [wgpu::ColorTargetState {
+            format: self.config.format,
+            blend: Some(descriptor.blend.into()),
+            write_mask: wgpu::ColorWrites::ALL,
+        }];
+
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let pipeline = self
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(&descriptor.label),
+                layout: Some(layout),
+                vertex: wgpu::VertexState {
+                    module: shader_module,
+                    entry_point: &descriptor.vertex_entry_point,
+                    buffers: &buffers,
+                },
+                fragment: descriptor.fragment_entry_point.as_ref().map(|entry_point| {
+                    wgpu::FragmentState {
+                        module: fragment_module.unwrap_or(shader_module),
+                        entry_point,
+                        targets: &targets,
+                    }
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: descriptor.topology.into(),
+                    strip_index_format: None,
+                    front_face: descriptor.front_face.into(),
+                    cull_mode: descriptor.cull_mode.into(),
+                    polygon_mode: descriptor.polygon_mode.into(),
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: descriptor.depth_stencil.map(Into::into),
+                multisample: descriptor.multisample.into(),
+                multiview: None,
+            });
+
+        match pollster::block_on(self.device.pop_error_scope()) {
+            Some(error) => Err(RendererError::Validation(error.to_string())),
+            None => Ok(pipeline),
+        }
+    }
+
+    /// Same as [`Renderer::CreatePipeline`], but reuses an equivalent pipeline when one was already built.
+    ///
+    /// Pipelines built from shader files are rebuilt by [`Renderer::ReloadShaders`].
+    pub fn GetPipeline(
+        &self,
+        descriptor: &PipelineDescriptor,
+        bind_group_layouts: &[&BindGroupLayout],
+    ) -> Result<PipelineHandle, RendererError> {
+        self.pipeline_cache
+            .GetOrCreate(descriptor, bind_group_layouts, self.config.format, || {
+                let layout = self.CreatePipelineLayout(
+                    &descriptor.label,
+                    &bind_group_layouts
+                        .iter()
+                        .map(|layout| layout.Get())
+                        .collect::<Vec<_>>(),
+                );
+                let pipeline = self.CreatePipelineWithLayout(descriptor, &layout)?;
+
+                Ok((pipeline, layout))
+            })
+    }
+
+    /// Same as [`Renderer::GetPipeline`] with the shader of `descriptor` replaced by a
+    /// permutation of `variant`, built from the module the variant caches for `key`.
+    ///
+    /// Each permutation is a separate cached pipeline and is hot reloaded like any other,
+    /// call [`ShaderVariant::Reload`] from [`State::ReloadShaders`](crate::State::ReloadShaders)
+    /// to refresh the modules of the variant as well.
+    pub fn GetVariantPipeline(
+        &self,
+        descriptor: &PipelineDescriptor,
+        variant: &ShaderVariant,
+        key: &ShaderVariantKey,
+        bind_group_layouts: &[&BindGroupLayout],
+    ) -> Result<PipelineHandle, RendererError> {
+        let descriptor = PipelineDescriptor {
+            shader: variant.GetPermutation(key)?,
+            ..descriptor.clone()
+        };
+
+        self.pipeline_cache
+            .GetOrCreate(&descriptor, bind_group_layouts, self.config.format, || {
+                let module = variant.GetModule(self, key)?;
+                let fragment_module = match &descriptor.fragment_shader {
+                    Some(shader) => Some(self.SubmitShader(shader)?),
+                    None => None,
+                };
+
+                let layout = self.CreatePipelineLayout(
+                    &descriptor.label,
+                    &bind_group_layouts
+                        .iter()
+                        .map(|layout| layout.Get())
+                        .collect::<Vec<_>>(),
+                );
+                let pipeline = self.CreatePipelineWithModules(
+                    &descriptor,
+                    &layout,
+                    &module,
+                    fragment_module.as_ref(),
+                )?;
+
+                Ok((pipeline, layout))
+            })
+    }
+
+    /// Rebuilds the cached pipelines using any of the changed shader files.
+    ///
+    /// Pipelines whose new shaders fail to compile keep running with the previous ones.
+    pub fn ReloadShaders(&self, changed: &[PathBuf]) -> usize {
+        self.pipeline_cache.Reload(changed, |descriptor, layout| {
+            self.CreatePipelineWithLayout(descriptor, layout)
+        })
     }
 
     pub fn SubmitVertexBuffer(&self, vertex_buffer: &VertexBuffer) -> wgpu::Buffer {
@@ -101,13 +610,10 @@ impl Renderer {
         pipeline: &wgpu::RenderPipeline,
         vertex_buffer: &wgpu::Buffer,
         index_buffer: &wgpu::Buffer,
+        index_format: IndexFormat,
         num_indices: u32,
-    ) -> Result<()> {
-        let output = self.surface.get_current_texture()?;
-
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+    ) -> Result<(), RendererError> {
+        let frame = self.GetCurrentFrame()?;
 
         let mut encoder = self
             .device
@@ -119,7 +625,7 @@ impl Renderer {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("RENDER_PASS"),
                 color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: &frame.view,
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -136,19 +642,252 @@ impl Renderer {
 
             render_pass.set_pipeline(pipeline);
             render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.set_index_buffer(index_buffer.slice(..), index_format.into());
             render_pass.draw_indexed(0..num_indices, 0, 0..1);
         }
 
         self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        frame.Present();
+
+        Ok(())
+    }
+
+    /// Reads back the contents of the offscreen color target.
+    ///
+    /// Only headless renderers can be captured. Surface textures only support the
+    /// `RENDER_ATTACHMENT` usage, so the swapchain of a window can't be copied, create the
+    /// renderer with [`Renderer::NewHeadless`] to capture frames.
+    pub fn CaptureFrame(&self) -> Result<image::RgbaImage> {
+        match &self.target {
+            Some(target) => target.Capture(&self.device, &self.queue),
+            None => bail!(
+                "Frame capture requires a headless renderer, window surfaces can't be read back"
+            ),
+        }
+    }
+
+    /// Captures the current frame and writes it to disk, headless renderers only.
+    ///
+    /// The image format is picked from the file extension, `.exr` files are stored as
+    /// 32 bit float images holding linear colors.
+    pub fn SaveFrame<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let frame = self.CaptureFrame()?;
+
+        let is_exr = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("exr"));
+
+        if is_exr {
+            ToLinearImage(&frame, self.config.format.describe().srgb).save(path)?;
+        } else {
+            frame.save(path)?;
+        }
 
         Ok(())
     }
 
+    /// Switches the window between windowed and fullscreen modes, no-op when headless.
+    pub fn SetWindowMode(&self, window_mode: WindowMode) {
+        if let Some(window) = &self.window {
+            let fullscreen =
+                window_mode.ToFullscreen(window.available_monitors(), window.primary_monitor());
+            window.set_fullscreen(fullscreen);
+        }
+    }
+
+    /// Toggles borderless fullscreen on the monitor the window currently is on.
+    pub fn ToggleFullscreen(&self) {
+        if let Some(window) = &self.window {
+            if window.fullscreen().is_some() {
+                window.set_fullscreen(None);
+            } else {
+                window.set_fullscreen(Some(Fullscreen::Borderless(window.current_monitor())));
+            }
+        }
+    }
+
+    pub fn IsFullscreen(&self) -> bool {
+        self.window
+            .as_ref()
+            .is_some_and(|window| window.fullscreen().is_some())
+    }
+
+    /// Changes the present mode, falling back to `Fifo` when the surface does not support it.
+    pub fn SetPresentMode(&mut self, present_mode: PresentMode) {
+        self.config.present_mode = present_mode.into();
+
+        if let Some(surface) = &self.surface {
+            Self::ConfigureSurface(surface, &self.device, &self.config);
+        }
+    }
+
+    /// Configures `surface`, logging the requested present mode.
+    ///
+    /// wgpu does not expose the modes a surface supports, it replaces unsupported ones
+    /// with `Fifo` and warns about it on its own.
+    fn ConfigureSurface(
+        surface: &wgpu::Surface,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+    ) {
+        log::info!("Presenting with {:?}", config.present_mode);
+        surface.configure(device, config);
+    }
+
+    /// Enables or disables vertical sync, disabling presents immediately.
+    pub fn SetVsync(&mut self, enabled: bool) {
+        self.SetPresentMode(if enabled {
+            PresentMode::Fifo
+        } else {
+            PresentMode::Immediate
+        });
+    }
+
+    /// Whether presenting waits for the vertical blank, so frames never tear.
+    pub fn IsVsync(&self) -> bool {
+        self.config.present_mode != wgpu::PresentMode::Immediate
+    }
+
     pub fn Resize(&mut self, width: u32, height: u32) {
         self.config.width = width;
         self.config.height = height;
-        self.surface.configure(&self.device, &self.config);
+
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.config);
+        }
+
+        if let Some(target) = &mut self.target {
+            *target = RenderTarget::New(&self.device, width, height, target.format);
+        }
+    }
+}
+
+#[cfg(test)]
+impl Renderer {
+    /// Small headless renderer for tests on the fallback adapter, panics without one.
+    ///
+    /// Tests using it are ignored by default, run them with `cargo test -- --ignored`.
+    pub(crate) fn NewForTests() -> Self {
+        pollster::block_on(Self::NewHeadless(4, 4, &RendererConfig::FallbackFromEnv()))
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+/// Reports a surface that kept failing after `failures` frames as a lost device.
+fn EscalateSurfaceError(error: wgpu::SurfaceError, failures: u32) -> RendererError {
+    if failures >= Renderer::MAX_SURFACE_FAILURES {
+        RendererError::DeviceLost(format!(
+            "surface still {:?} after {} reconfigures",
+            error,
+            failures - 1
+        ))
+    } else {
+        error.into()
+    }
+}
+
+/// Converts 8 bit pixels to floats, decoding the sRGB transfer function of sRGB targets.
+fn ToLinearImage(image: &image::RgbaImage, srgb: bool) -> image::Rgba32FImage {
+    image::Rgba32FImage::from_fn(image.width(), image.height(), |x, y| {
+        let pixel = image.get_pixel(x, y).0;
+
+        if srgb {
+            let color = RgbaColor::FromSrgb8(pixel);
+            image::Rgba([color.red, color.green, color.blue, color.alpha])
+        } else {
+            image::Rgba(pixel.map(|value| value as f32 / 255.0))
+        }
+    })
+}
+
+/// Lists the names of the `required` limits that exceed the `supported` ones.
+fn GetUnsupportedLimits(required: &wgpu::Limits, supported: &wgpu::Limits) -> Vec<&'static str> {
+    let mut unsupported = Vec::new();
+
+    macro_rules! check {
+        (max: $($name:ident),*; min: $($min_name:ident),*) => {
+            $(
+                if required.$name > supported.$name {
+                    unsupported.push(stringify!($name));
+                }
+            )*
+            $(
+                if required.$min_name < supported.$min_name {
+                    unsupported.push(stringify!($min_name));
+                }
+            )*
+        };
+    }
+
+    check!(
+        max:
+            max_texture_dimension_1d,
+            max_texture_dimension_2d,
+            max_texture_dimension_3d,
+            max_texture_array_layers,
+            max_bind_groups,
+            max_dynamic_uniform_buffers_per_pipeline_layout,
+            max_dynamic_storage_buffers_per_pipeline_layout,
+            max_sampled_textures_per_shader_stage,
+            max_samplers_per_shader_stage,
+            max_storage_buffers_per_shader_stage,
+            max_storage_textures_per_shader_stage,
+            max_uniform_buffers_per_shader_stage,
+            max_uniform_buffer_binding_size,
+            max_storage_buffer_binding_size,
+            max_vertex_buffers,
+            max_vertex_attributes,
+            max_vertex_buffer_array_stride,
+            max_push_constant_size,
+            max_inter_stage_shader_components,
+            max_compute_workgroup_storage_size,
+            max_compute_invocations_per_workgroup,
+            max_compute_workgroup_size_x,
+            max_compute_workgroup_size_y,
+            max_compute_workgroup_size_z,
+            max_compute_workgroups_per_dimension;
+        min:
+            min_uniform_buffer_offset_alignment,
+            min_storage_buffer_offset_alignment
+    );
+
+    unsupported
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn SurfaceFailuresEscalateToDeviceLost() {
+        for failures in 1..Renderer::MAX_SURFACE_FAILURES {
+            assert!(matches!(
+                EscalateSurfaceError(wgpu::SurfaceError::Lost, failures),
+                RendererError::Surface(wgpu::SurfaceError::Lost)
+            ));
+        }
+
+        assert!(matches!(
+            EscalateSurfaceError(wgpu::SurfaceError::Outdated, Renderer::MAX_SURFACE_FAILURES),
+            RendererError::DeviceLost(_)
+        ));
+        assert!(matches!(
+            EscalateSurfaceError(wgpu::SurfaceError::Timeout, 0),
+            RendererError::Surface(wgpu::SurfaceError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn ToLinearImageDecodesSrgb() {
+        let image = image::RgbaImage::from_pixel(1, 1, image::Rgba([188, 0, 255, 128]));
+
+        let [red, green, blue, alpha] = ToLinearImage(&image, true).get_pixel(0, 0).0;
+        assert!((red - 0.5).abs() < 0.01);
+        assert_eq!([green, blue], [0.0, 1.0]);
+        assert!((alpha - 128.0 / 255.0).abs() < 1e-6);
+
+        let [red, ..] = ToLinearImage(&image, false).get_pixel(0, 0).0;
+        assert!((red - 188.0 / 255.0).abs() < 1e-6);
     }
 }
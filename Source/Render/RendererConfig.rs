@@ -0,0 +1,297 @@
+use super::{Backend, LimitsPreset, PowerPreference};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Controls how the renderer picks its adapter and creates the device.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RendererConfig {
+    /// Backends to look for adapters on, empty allows every backend.
+    pub backends: Vec<Backend>,
+
+    /// Case insensitive part of the adapter name, e.g. `"nvidia"`.
+    pub adapter_name: Option<String>,
+
+    /// Index into the list of compatible adapters, applied after name and fallback
+    /// filtering. Takes precedence over `power_preference`.
+    pub adapter_index: Option<usize>,
+
+    /// Picks between the adapters left after filtering when no `adapter_index` is set.
+    pub power_preference: PowerPreference,
+
+    /// Forces the software fallback adapter, useful on machines without a GPU.
+    ///
+    /// With `adapter_name` or `adapter_index` only CPU adapters are considered.
+    pub force_fallback_adapter: bool,
+
+    /// Features the device must support, serialized as raw bits.
+    #[serde(serialize_with = "SerializeFeatures")]
+    #[serde(deserialize_with = "DeserializeFeatures")]
+    pub required_features: wgpu::Features,
+
+    /// Features enabled only when the adapter supports them, serialized as raw bits.
+    #[serde(serialize_with = "SerializeFeatures")]
+    #[serde(deserialize_with = "DeserializeFeatures")]
+    pub optional_features: wgpu::Features,
+
+    pub limits: LimitsPreset,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            backends: Vec::new(),
+            adapter_name: None,
+            adapter_index: None,
+            power_preference: PowerPreference::default(),
+            force_fallback_adapter: false,
+            required_features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
+            limits: LimitsPreset::default(),
+        }
+    }
+}
+
+impl RendererConfig {
+    /// Creates the default configuration overridden by environment variables.
+    ///
+    /// | Variable | Example |
+    /// |---|---|
+    /// | `WGPU_BACKEND` | `vulkan,gl` |
+    /// | `WGPU_ADAPTER_NAME` | `nvidia` |
+    /// | `WGPU_ADAPTER_INDEX` | `1` |
+    /// | `WGPU_POWER_PREF` | `low` or `high` |
+    /// | `WGPU_FORCE_FALLBACK_ADAPTER` | `1` |
+    pub fn FromEnv() -> Self {
+        let mut config = Self::default();
+
+        if let Ok(names) = std::env::var("WGPU_BACKEND") {
+            config.backends = Self::ParseBackends(&names);
+
+            if config.backends.is_empty() {
+                log::warn!(
+                    "WGPU_BACKEND {:?} names no known backend, using all backends",
+                    names
+                );
+            }
+        }
+
+        if let Ok(name) = std::env::var("WGPU_ADAPTER_NAME") {
+            config.adapter_name = Some(name);
+        }
+
+        if let Ok(index) = std::env::var("WGPU_ADAPTER_INDEX") {
+            match index.trim().parse() {
+                Ok(index) => config.adapter_index = Some(index),
+                Err(_) => log::warn!("Ignoring invalid WGPU_ADAPTER_INDEX {:?}", index),
+            }
+        }
+
+        if let Ok(power_preference) = std::env::var("WGPU_POWER_PREF") {
+            match power_preference.trim().to_lowercase().as_str() {
+                "low" => config.power_preference = PowerPreference::LowPower,
+                "high" => config.power_preference = PowerPreference::HighPerformance,
+                _ => log::warn!("Ignoring invalid WGPU_POWER_PREF {:?}", power_preference),
+            }
+        }
+
+        if let Ok(fallback) = std::env::var("WGPU_FORCE_FALLBACK_ADAPTER") {
+            config.force_fallback_adapter = matches!(
+                fallback.trim().to_lowercase().as_str(),
+                "1" | "true" | "yes"
+            );
+        }
+
+        config
+    }
+
+    /// Like [`RendererConfig::FromEnv`], but forces the fallback adapter unless
+    /// `WGPU_FORCE_FALLBACK_ADAPTER` is set, so tests render the same on CI software
+    /// rasterizers as on developer machines.
+    pub fn FallbackFromEnv() -> Self {
+        let mut config = Self::FromEnv();
+
+        if std::env::var_os("WGPU_FORCE_FALLBACK_ADAPTER").is_none() {
+            config.force_fallback_adapter = true;
+        }
+
+        config
+    }
+
+    /// Parses a comma separated list of backend names, unknown names are skipped with a warning.
+    pub fn ParseBackends(names: &str) -> Vec<Backend> {
+        names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .filter_map(|name| {
+                let backend = Backend::FromName(name);
+
+                if backend.is_none() {
+                    log::warn!("Ignoring unknown backend {:?}", name);
+                }
+
+                backend
+            })
+            .collect()
+    }
+
+    /// Picks an adapter by name and index, returning its position in `adapters`.
+    ///
+    /// Adapters are filtered by `adapter_name` and, when forcing the fallback adapter, by
+    /// being CPU adapters. `adapter_index` then selects one of them, otherwise the first
+    /// adapter best matching `power_preference` is taken.
+    pub fn SelectAdapter(&self, adapters: &[wgpu::AdapterInfo]) -> Option<usize> {
+        let name = self.adapter_name.as_deref().map(str::to_lowercase);
+
+        let mut candidates = adapters
+            .iter()
+            .enumerate()
+            .filter(|(_, info)| {
+                name.as_ref()
+                    .is_none_or(|name| info.name.to_lowercase().contains(name))
+            })
+            .filter(|(_, info)| {
+                !self.force_fallback_adapter || info.device_type == wgpu::DeviceType::Cpu
+            });
+
+        match self.adapter_index {
+            Some(index) => candidates.nth(index).map(|(i, _)| i),
+            None => candidates
+                .min_by_key(|(_, info)| self.GetPreferenceRank(info.device_type))
+                .map(|(i, _)| i),
+        }
+    }
+
+    /// Features to enable on a device created from `adapter`.
+    pub fn GetFeatures(&self, adapter: &wgpu::Adapter) -> wgpu::Features {
+        self.required_features | (self.optional_features & adapter.features())
+    }
+
+    pub fn GetBackends(&self) -> wgpu::Backends {
+        if self.backends.is_empty() {
+            wgpu::Backends::all()
+        } else {
+            self.backends
+                .iter()
+                .fold(wgpu::Backends::empty(), |backends, backend| {
+                    backends | (*backend).into()
+                })
+        }
+    }
+
+    /// Lower is better.
+    fn GetPreferenceRank(&self, device_type: wgpu::DeviceType) -> u32 {
+        match (self.power_preference, device_type) {
+            (PowerPreference::HighPerformance, wgpu::DeviceType::DiscreteGpu) => 0,
+            (PowerPreference::LowPower, wgpu::DeviceType::IntegratedGpu) => 0,
+            (_, wgpu::DeviceType::DiscreteGpu | wgpu::DeviceType::IntegratedGpu) => 1,
+            (_, wgpu::DeviceType::VirtualGpu) => 2,
+            (_, wgpu::DeviceType::Cpu | wgpu::DeviceType::Other) => 3,
+        }
+    }
+}
+
+fn SerializeFeatures<S: Serializer>(
+    features: &wgpu::Features,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    features.bits().serialize(serializer)
+}
+
+fn DeserializeFeatures<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<wgpu::Features, D::Error> {
+    Ok(wgpu::Features::from_bits_truncate(u64::deserialize(
+        deserializer,
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn Adapter(name: &str, device_type: wgpu::DeviceType) -> wgpu::AdapterInfo {
+        wgpu::AdapterInfo {
+            name: name.to_string(),
+            vendor: 0,
+            device: 0,
+            device_type,
+            backend: wgpu::Backend::Vulkan,
+        }
+    }
+
+    fn Adapters() -> Vec<wgpu::AdapterInfo> {
+        vec![
+            Adapter("Intel UHD Graphics", wgpu::DeviceType::IntegratedGpu),
+            Adapter("NVIDIA GeForce RTX", wgpu::DeviceType::DiscreteGpu),
+            Adapter("llvmpipe", wgpu::DeviceType::Cpu),
+            Adapter("NVIDIA Quadro", wgpu::DeviceType::DiscreteGpu),
+        ]
+    }
+
+    #[test]
+    fn ParseBackendsSkipsUnknownNames() {
+        assert_eq!(
+            RendererConfig::ParseBackends("vulkan, metl,GL,"),
+            vec![Backend::Vulkan, Backend::Gl]
+        );
+        assert!(RendererConfig::ParseBackends("vulcan").is_empty());
+    }
+
+    #[test]
+    fn SelectAdapterByNameUsesPowerPreference() {
+        let config = RendererConfig {
+            adapter_name: Some("nvidia".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(config.SelectAdapter(&Adapters()), Some(1));
+    }
+
+    #[test]
+    fn SelectAdapterPrefersIntegratedForLowPower() {
+        let config = RendererConfig {
+            adapter_name: Some("i".to_string()),
+            power_preference: PowerPreference::LowPower,
+            ..Default::default()
+        };
+
+        assert_eq!(config.SelectAdapter(&Adapters()), Some(0));
+    }
+
+    #[test]
+    fn SelectAdapterIndexAppliesAfterFiltering() {
+        let config = RendererConfig {
+            adapter_name: Some("NVIDIA".to_string()),
+            adapter_index: Some(1),
+            ..Default::default()
+        };
+
+        assert_eq!(config.SelectAdapter(&Adapters()), Some(3));
+
+        let config = RendererConfig {
+            adapter_index: Some(2),
+            ..config
+        };
+
+        assert_eq!(config.SelectAdapter(&Adapters()), None);
+    }
+
+    #[test]
+    fn SelectAdapterForcingFallbackOnlyConsidersCpu() {
+        let config = RendererConfig {
+            adapter_index: Some(0),
+            force_fallback_adapter: true,
+            ..Default::default()
+        };
+
+        assert_eq!(config.SelectAdapter(&Adapters()), Some(2));
+
+        let config = RendererConfig {
+            adapter_name: Some("nvidia".to_string()),
+            ..config
+        };
+
+        assert_eq!(config.SelectAdapter(&Adapters()), None);
+    }
+}
@@ -0,0 +1,165 @@
+use super::VertexLayoutError;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Errors raised while creating or drawing with a [`Renderer`](super::Renderer).
+#[derive(Debug)]
+pub enum RendererError {
+    /// No adapter matched the renderer configuration.
+    NoAdapter {
+        available: Vec<String>,
+    },
+
+    /// The adapter can't present to the window surface.
+    IncompatibleSurface,
+
+    /// The adapter lacks some of the required features.
+    MissingFeatures(wgpu::Features),
+
+    /// The adapter doesn't reach the requested limits, listed by name.
+    UnsupportedLimits(Vec<&'static str>),
+
+    RequestDevice(wgpu::RequestDeviceError),
+
+    /// Acquiring the next frame failed, the surface may need to be reconfigured.
+    Surface(wgpu::SurfaceError),
+
+    /// The device stopped working and must be recreated with all of its resources.
+    ///
+    /// wgpu does not report device loss directly, it is assumed once the surface keeps
+    /// failing after being reconfigured, see [`Renderer::GetCurrentFrame`](super::Renderer::GetCurrentFrame).
+    DeviceLost(String),
+
+    /// The device ran out of memory for a resource.
+    OutOfMemory(String),
+
+    /// A wgpu call was invalid.
+    Validation(String),
+
+    ShaderCompilation {
+        label: String,
+        message: String,
+    },
+
+    /// The vertex buffer layouts don't match the vertex shader inputs.
+    VertexLayout(Vec<VertexLayoutError>),
+
+    AssetLoad {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl fmt::Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAdapter { available } if available.is_empty() => {
+                write!(f, "No adapter found, none are available")
+            }
+            Self::NoAdapter { available } => {
+                write!(
+                    f,
+                    "No adapter matches the configuration, available adapters:"
+                )?;
+
+                for (i, adapter) in available.iter().enumerate() {
+                    write!(f, "\n  [{}] {}", i, adapter)?;
+                }
+
+                Ok(())
+            }
+            Self::IncompatibleSurface => write!(f, "Adapter is not compatible with the surface"),
+            Self::MissingFeatures(features) => {
+                write!(
+                    f,
+                    "Adapter does not support the required features {:?}",
+                    features
+                )
+            }
+            Self::UnsupportedLimits(limits) => {
+                write!(
+                    f,
+                    "Adapter does not support the limits {}",
+                    limits.join(", ")
+                )
+            }
+            Self::RequestDevice(error) => write!(f, "Failed to request device: {}", error),
+            Self::Surface(error) => write!(f, "Surface error: {}", error),
+            Self::DeviceLost(message) => write!(f, "Device lost: {}", message),
+            Self::OutOfMemory(message) => write!(f, "Out of memory: {}", message),
+            Self::Validation(message) => write!(f, "Validation error: {}", message),
+            Self::ShaderCompilation { label, message } => {
+                write!(f, "Failed to compile shader {:?}: {}", label, message)
+            }
+            Self::VertexLayout(errors) => {
+                write!(f, "Vertex layouts don't match the shader:")?;
+
+                for error in errors {
+                    write!(f, "\n  {}", error)?;
+                }
+
+                Ok(())
+            }
+            Self::AssetLoad { path, message } => {
+                write!(f, "Failed to load {:?}: {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RendererError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::RequestDevice(error) => Some(error),
+            Self::Surface(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<wgpu::SurfaceError> for RendererError {
+    fn from(error: wgpu::SurfaceError) -> Self {
+        Self::Surface(error)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for RendererError {
+    fn from(error: wgpu::RequestDeviceError) -> Self {
+        Self::RequestDevice(error)
+    }
+}
+
+impl From<wgpu::Error> for RendererError {
+    fn from(error: wgpu::Error) -> Self {
+        match error {
+            wgpu::Error::OutOfMemory { .. } => Self::OutOfMemory(error.to_string()),
+            wgpu::Error::Validation { description, .. } => Self::Validation(description),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn OutOfMemoryIsNotDeviceLoss() {
+        let error = RendererError::from(wgpu::Error::OutOfMemory {
+            source: Box::new(std::fmt::Error),
+        });
+
+        assert!(matches!(error, RendererError::OutOfMemory(_)));
+    }
+
+    #[test]
+    fn ValidationKeepsDescription() {
+        let error = RendererError::from(wgpu::Error::Validation {
+            source: Box::new(std::fmt::Error),
+            description: "Buffer is too small".to_string(),
+        });
+
+        assert!(
+            matches!(error, RendererError::Validation(description) if description == "Buffer is too small")
+        );
+    }
+}
@@ -1,10 +1,10 @@
-use anyhow::*;
+use super::{MipmapGenerator, MipmapGeneratorCache, MipmapMode, RendererError, TextureOptions};
 use image::GenericImageView;
 use std::path::Path;
 
 /// Texture coordinates.
 ///
-/// ```no_run
+/// ```text
 ///  (0.0)               (1.0)
 ///   V1 ----------------- V2
 ///   |                   / |
@@ -29,32 +29,70 @@ impl Texture {
     pub fn Load<P: AsRef<Path>>(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
+        generators: &MipmapGeneratorCache,
         path: P,
-    ) -> Result<Self> {
-        // Needed to appease the borrow checker
-        let path_copy = path.as_ref().to_path_buf();
-        let label = path_copy.to_str();
-        let img = image::open(path)?;
+    ) -> Result<Self, RendererError> {
+        Self::LoadWith(device, queue, generators, path, &TextureOptions::default())
+    }
 
-        Self::FromImage(device, queue, &img, label)
+    pub fn LoadWith<P: AsRef<Path>>(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        generators: &MipmapGeneratorCache,
+        path: P,
+        options: &TextureOptions,
+    ) -> Result<Self, RendererError> {
+        let path = path.as_ref();
+
+        let img = image::open(path).map_err(|e| RendererError::AssetLoad {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+
+        Self::FromImageWith(device, queue, generators, &img, path.to_str(), options)
     }
 
     pub fn FromBytes(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
+        generators: &MipmapGeneratorCache,
         bytes: &[u8],
         label: &str,
-    ) -> Result<Self> {
-        let img = image::load_from_memory(bytes)?;
-        Self::FromImage(device, queue, &img, Some(label))
+    ) -> Result<Self, RendererError> {
+        let img = image::load_from_memory(bytes).map_err(|e| RendererError::AssetLoad {
+            path: label.into(),
+            message: e.to_string(),
+        })?;
+
+        Self::FromImage(device, queue, generators, &img, Some(label))
     }
 
     pub fn FromImage(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
+        generators: &MipmapGeneratorCache,
+        img: &image::DynamicImage,
+        label: Option<&str>,
+    ) -> Result<Self, RendererError> {
+        Self::FromImageWith(
+            device,
+            queue,
+            generators,
+            img,
+            label,
+            &TextureOptions::default(),
+        )
+    }
+
+    /// Uploads the image, generating its mip chain as selected by `options`.
+    pub fn FromImageWith(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        generators: &MipmapGeneratorCache,
         img: &image::DynamicImage,
         label: Option<&str>,
-    ) -> Result<Self> {
+        options: &TextureOptions,
+    ) -> Result<Self, RendererError> {
         let rgba = img.to_rgba8();
         let dimensions = img.dimensions();
 
@@ -64,41 +102,49 @@ impl Texture {
             depth_or_array_layers: 1,
         };
 
+        let format = options.GetFormat();
+        let mip_level_count = options.GetMipLevelCount(dimensions.0, dimensions.1);
+        let is_gpu_generated = options.mipmaps == MipmapMode::Gpu && mip_level_count > 1;
+
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        if is_gpu_generated {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
+
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label,
             size,
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            format,
+            usage,
         });
 
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                aspect: wgpu::TextureAspect::All,
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-            },
-            &rgba,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: std::num::NonZeroU32::new(4 * dimensions.0),
-                rows_per_image: std::num::NonZeroU32::new(dimensions.1),
-            },
-            size,
-        );
+        Self::WriteLevel(queue, &texture, 0, &rgba);
+
+        if is_gpu_generated {
+            generators
+                .Get(device, format)
+                .Generate(device, queue, &texture, mip_level_count);
+        } else if mip_level_count > 1 {
+            let levels = MipmapGenerator::GenerateLevels(&rgba, options.srgb, mip_level_count);
+
+            for (level, img) in levels.iter().enumerate() {
+                Self::WriteLevel(queue, &texture, level as u32 + 1, img);
+            }
+        }
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            label,
+            address_mode_u: options.address_mode_u.into(),
+            address_mode_v: options.address_mode_v.into(),
             address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
+            mag_filter: options.mag_filter.into(),
+            min_filter: options.min_filter.into(),
+            mipmap_filter: options.mipmap_filter.into(),
             ..Default::default()
         });
 
@@ -109,6 +155,26 @@ impl Texture {
         })
     }
 
+    /// Creates a 1x1 texture of a single sRGB color, used as a stand-in for missing textures.
+    pub fn FromColor(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        color: [u8; 4],
+        label: &str,
+    ) -> Result<Self, RendererError> {
+        let img =
+            image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(1, 1, image::Rgba(color)));
+
+        // A single level never reaches the generators
+        Self::FromImage(
+            device,
+            queue,
+            &MipmapGeneratorCache::New(),
+            &img,
+            Some(label),
+        )
+    }
+
     pub fn CreateDepthTexture(
         device: &wgpu::Device,
         config: &wgpu::SurfaceConfiguration,
@@ -153,4 +219,33 @@ impl Texture {
             sampler,
         }
     }
+
+    fn WriteLevel(
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+        level: u32,
+        img: &image::RgbaImage,
+    ) {
+        let (width, height) = img.dimensions();
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                aspect: wgpu::TextureAspect::All,
+                texture,
+                mip_level: level,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            img,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(4 * width),
+                rows_per_image: std::num::NonZeroU32::new(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
 }
@@ -0,0 +1,97 @@
+use super::{AddressMode, FilterMode, MipmapMode};
+use serde::{Deserialize, Serialize};
+
+/// Settings used when uploading an image with [`Texture::FromImageWith`](super::Texture::FromImageWith).
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextureOptions {
+    /// Whether the image holds sRGB encoded colors, disable for normal maps and other data.
+    pub srgb: bool,
+    pub mipmaps: MipmapMode,
+
+    /// Sampler behavior for texture coordinates outside of `[0, 1]`, per axis.
+    pub address_mode_u: AddressMode,
+    pub address_mode_v: AddressMode,
+
+    pub mag_filter: FilterMode,
+    pub min_filter: FilterMode,
+    /// Filter between mip levels.
+    pub mipmap_filter: FilterMode,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            srgb: true,
+            mipmaps: MipmapMode::default(),
+            address_mode_u: AddressMode::default(),
+            address_mode_v: AddressMode::default(),
+            mag_filter: FilterMode::default(),
+            min_filter: FilterMode::default(),
+            mipmap_filter: FilterMode::default(),
+        }
+    }
+}
+
+impl TextureOptions {
+    /// Options for images holding data instead of colors, such as normal maps.
+    pub fn Linear() -> Self {
+        Self {
+            srgb: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn WithMipmaps(mut self, mipmaps: MipmapMode) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    /// Uses `address_mode` along both axes.
+    pub fn WithAddressMode(mut self, address_mode: AddressMode) -> Self {
+        self.address_mode_u = address_mode;
+        self.address_mode_v = address_mode;
+        self
+    }
+
+    pub fn GetFormat(&self) -> wgpu::TextureFormat {
+        if self.srgb {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        }
+    }
+
+    /// Number of mip levels of a texture of the given size, down to 1x1.
+    pub fn GetMipLevelCount(&self, width: u32, height: u32) -> u32 {
+        match self.mipmaps {
+            MipmapMode::Disabled => 1,
+            _ => 32 - width.max(height).max(1).leading_zeros(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn MipLevelCountReachesOneTexel() {
+        let options = TextureOptions::default();
+
+        assert_eq!(options.GetMipLevelCount(1, 1), 1);
+        assert_eq!(options.GetMipLevelCount(256, 1), 9);
+        assert_eq!(options.GetMipLevelCount(300, 200), 9);
+        assert_eq!(options.GetMipLevelCount(256, 256), 9);
+        assert_eq!(options.GetMipLevelCount(257, 1), 9);
+        assert_eq!(options.GetMipLevelCount(1, 512), 10);
+        assert_eq!(options.GetMipLevelCount(0, 0), 1);
+    }
+
+    #[test]
+    fn DisabledMipmapsUseOneLevel() {
+        let options = TextureOptions::default().WithMipmaps(MipmapMode::Disabled);
+
+        assert_eq!(options.GetMipLevelCount(300, 200), 1);
+    }
+}
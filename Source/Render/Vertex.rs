@@ -1,5 +1,8 @@
 use super::VertexBufferLayout;
 
+/// Derive with `#[derive(Vertex)]` to compute the layout from the struct fields.
+pub use GraphicsDerive::Vertex;
+
 pub trait Vertex {
     fn GetLayout() -> VertexBufferLayout;
 }
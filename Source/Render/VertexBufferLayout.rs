@@ -1,11 +1,157 @@
-use super::{StepMode, VertexAttribute};
+use super::{StepMode, VertexAttribute, VertexLayoutError};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 
-#[derive(Clone, Debug, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Default, Serialize, Deserialize)]
 pub struct VertexBufferLayout {
     pub label: Cow<'static, str>,
     pub stride: usize,
     pub step_mode: StepMode,
     pub attributes: Vec<VertexAttribute>,
 }
+
+impl VertexBufferLayout {
+    /// Checks that the attributes are aligned, fit in the stride and don't overlap.
+    pub fn Validate(&self) -> Vec<VertexLayoutError> {
+        let mut errors = Vec::new();
+
+        for attribute in &self.attributes {
+            let size = attribute.format.GetSize() as usize;
+
+            if attribute.offset % size.min(4) != 0 {
+                errors.push(VertexLayoutError::Misaligned {
+                    layout: self.label.clone(),
+                    attribute: attribute.label.clone(),
+                    offset: attribute.offset,
+                });
+            }
+
+            // A zero stride repeats the same element for every vertex
+            if self.stride != 0 && attribute.offset + size > self.stride {
+                errors.push(VertexLayoutError::OutOfBounds {
+                    layout: self.label.clone(),
+                    attribute: attribute.label.clone(),
+                    offset: attribute.offset,
+                    size,
+                    stride: self.stride,
+                });
+            }
+        }
+
+        let mut attributes = self.attributes.iter().collect::<Vec<_>>();
+        attributes.sort_by_key(|attribute| attribute.offset);
+
+        for pair in attributes.windows(2) {
+            if pair[0].offset + pair[0].format.GetSize() as usize > pair[1].offset {
+                errors.push(VertexLayoutError::Overlap {
+                    layout: self.label.clone(),
+                    first: pair[0].label.clone(),
+                    second: pair[1].label.clone(),
+                });
+            }
+        }
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Render::VertexFormat;
+
+    fn Attribute(label: &'static str, format: VertexFormat, offset: usize) -> VertexAttribute {
+        VertexAttribute {
+            label: label.into(),
+            format,
+            offset,
+            shader_location: 0,
+        }
+    }
+
+    fn Layout(stride: usize, attributes: Vec<VertexAttribute>) -> VertexBufferLayout {
+        VertexBufferLayout {
+            label: "Layout".into(),
+            stride,
+            step_mode: StepMode::Vertex,
+            attributes,
+        }
+    }
+
+    #[test]
+    fn ValidLayoutHasNoErrors() {
+        let layout = Layout(
+            20,
+            vec![
+                Attribute("Position", VertexFormat::Float32x3, 0),
+                Attribute("Color", VertexFormat::UNorm8x4, 12),
+                Attribute("Uv", VertexFormat::UNorm16x2, 16),
+            ],
+        );
+
+        assert!(layout.Validate().is_empty());
+    }
+
+    #[test]
+    fn MisalignedOffset() {
+        let layout = Layout(16, vec![Attribute("Position", VertexFormat::Float32x3, 2)]);
+
+        assert_eq!(
+            layout.Validate(),
+            vec![VertexLayoutError::Misaligned {
+                layout: "Layout".into(),
+                attribute: "Position".into(),
+                offset: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn AttributePastStride() {
+        let layout = Layout(
+            16,
+            vec![
+                Attribute("Position", VertexFormat::Float32x3, 0),
+                Attribute("Uv", VertexFormat::Float32x2, 12),
+            ],
+        );
+
+        assert_eq!(
+            layout.Validate(),
+            vec![VertexLayoutError::OutOfBounds {
+                layout: "Layout".into(),
+                attribute: "Uv".into(),
+                offset: 12,
+                size: 8,
+                stride: 16,
+            }]
+        );
+    }
+
+    #[test]
+    fn ZeroStrideIsNotOutOfBounds() {
+        let layout = Layout(0, vec![Attribute("Color", VertexFormat::Float32x4, 0)]);
+
+        assert!(layout.Validate().is_empty());
+    }
+
+    #[test]
+    fn OverlappingAttributes() {
+        let layout = Layout(
+            24,
+            vec![
+                Attribute("Normal", VertexFormat::Float32x3, 8),
+                Attribute("Position", VertexFormat::Float32x3, 0),
+            ],
+        );
+
+        assert_eq!(
+            layout.Validate(),
+            vec![VertexLayoutError::Overlap {
+                layout: "Layout".into(),
+                first: "Position".into(),
+                second: "Normal".into(),
+            }]
+        );
+    }
+}
@@ -119,7 +119,7 @@ impl VertexFormat {
             Self::Norm8x2 => 2,
             Self::Norm8x4 => 4,
             Self::UInt16x2 => 2 * 2,
-            Self::UInt16x4 => 4 * 4,
+            Self::UInt16x4 => 2 * 4,
             Self::Int16x2 => 2 * 2,
             Self::Int16x4 => 2 * 4,
             Self::UNorm16x2 => 2 * 2,
@@ -146,6 +146,60 @@ impl VertexFormat {
             Self::Float64x4 => 8 * 4,
         }
     }
+
+    /// Checks whether the format can feed a shader input of the `input` format.
+    ///
+    /// Like wgpu, only the scalar kind has to match, missing components are filled in.
+    pub fn IsCompatibleWith(&self, input: VertexFormat) -> bool {
+        self.GetScalarKind() == input.GetScalarKind()
+    }
+
+    pub(crate) fn GetScalarKind(&self) -> naga::ScalarKind {
+        match *self {
+            Self::UInt8x2
+            | Self::UInt8x4
+            | Self::UInt16x2
+            | Self::UInt16x4
+            | Self::UInt32
+            | Self::UInt32x2
+            | Self::UInt32x3
+            | Self::UInt32x4 => naga::ScalarKind::Uint,
+            Self::Int8x2
+            | Self::Int8x4
+            | Self::Int16x2
+            | Self::Int16x4
+            | Self::Int32
+            | Self::Int32x2
+            | Self::Int32x3
+            | Self::Int32x4 => naga::ScalarKind::Sint,
+            _ => naga::ScalarKind::Float,
+        }
+    }
+
+    /// Returns the 32 or 64 bit format matching a shader scalar or vector type.
+    pub(crate) fn FromShaderType(kind: naga::ScalarKind, width: u8, size: u32) -> Option<Self> {
+        let format = match (kind, width, size) {
+            (naga::ScalarKind::Float, 4, 1) => Self::Float32,
+            (naga::ScalarKind::Float, 4, 2) => Self::Float32x2,
+            (naga::ScalarKind::Float, 4, 3) => Self::Float32x3,
+            (naga::ScalarKind::Float, 4, 4) => Self::Float32x4,
+            (naga::ScalarKind::Float, 8, 1) => Self::Float64,
+            (naga::ScalarKind::Float, 8, 2) => Self::Float64x2,
+            (naga::ScalarKind::Float, 8, 3) => Self::Float64x3,
+            (naga::ScalarKind::Float, 8, 4) => Self::Float64x4,
+            (naga::ScalarKind::Uint, 4, 1) => Self::UInt32,
+            (naga::ScalarKind::Uint, 4, 2) => Self::UInt32x2,
+            (naga::ScalarKind::Uint, 4, 3) => Self::UInt32x3,
+            (naga::ScalarKind::Uint, 4, 4) => Self::UInt32x4,
+            (naga::ScalarKind::Sint, 4, 1) => Self::Int32,
+            (naga::ScalarKind::Sint, 4, 2) => Self::Int32x2,
+            (naga::ScalarKind::Sint, 4, 3) => Self::Int32x3,
+            (naga::ScalarKind::Sint, 4, 4) => Self::Int32x4,
+            _ => return None,
+        };
+
+        Some(format)
+    }
 }
 
 impl From<VertexFormat> for wgpu::VertexFormat {
@@ -188,3 +242,64 @@ impl From<VertexFormat> for wgpu::VertexFormat {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn UInt16x4IsEightBytes() {
+        assert_eq!(VertexFormat::UInt16x4.GetSize(), 8);
+        assert_eq!(
+            wgpu::VertexFormat::from(VertexFormat::UInt16x4),
+            wgpu::VertexFormat::Uint16x4
+        );
+    }
+
+    #[test]
+    fn SizesMatchWgpu() {
+        for format in [
+            VertexFormat::UInt8x2,
+            VertexFormat::UInt8x4,
+            VertexFormat::Int8x2,
+            VertexFormat::Int8x4,
+            VertexFormat::UNorm8x2,
+            VertexFormat::UNorm8x4,
+            VertexFormat::Norm8x2,
+            VertexFormat::Norm8x4,
+            VertexFormat::UInt16x2,
+            VertexFormat::UInt16x4,
+            VertexFormat::Int16x2,
+            VertexFormat::Int16x4,
+            VertexFormat::UNorm16x2,
+            VertexFormat::UNorm16x4,
+            VertexFormat::Norm16x2,
+            VertexFormat::Norm16x4,
+            VertexFormat::Float16x2,
+            VertexFormat::Float16x4,
+            VertexFormat::Float32,
+            VertexFormat::Float32x2,
+            VertexFormat::Float32x3,
+            VertexFormat::Float32x4,
+            VertexFormat::UInt32,
+            VertexFormat::UInt32x2,
+            VertexFormat::UInt32x3,
+            VertexFormat::UInt32x4,
+            VertexFormat::Int32,
+            VertexFormat::Int32x2,
+            VertexFormat::Int32x3,
+            VertexFormat::Int32x4,
+            VertexFormat::Float64,
+            VertexFormat::Float64x2,
+            VertexFormat::Float64x3,
+            VertexFormat::Float64x4,
+        ] {
+            assert_eq!(
+                format.GetSize(),
+                wgpu::VertexFormat::from(format).size(),
+                "{:?}",
+                format
+            );
+        }
+    }
+}
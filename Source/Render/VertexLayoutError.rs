@@ -0,0 +1,114 @@
+use super::VertexFormat;
+use std::borrow::Cow;
+use std::fmt;
+
+/// Mismatch between vertex buffer layouts and the inputs of a vertex shader.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VertexLayoutError {
+    /// A shader input has no attribute at its location.
+    MissingLocation { input: String, location: u32 },
+
+    /// The attribute format has a different scalar kind than the shader input.
+    IncompatibleFormat {
+        attribute: Cow<'static, str>,
+        location: u32,
+        format: VertexFormat,
+        expected: VertexFormat,
+    },
+
+    /// Two attributes use the same shader location.
+    DuplicateLocation {
+        location: u32,
+        first: Cow<'static, str>,
+        second: Cow<'static, str>,
+    },
+
+    /// The attribute ends past the stride of its layout.
+    OutOfBounds {
+        layout: Cow<'static, str>,
+        attribute: Cow<'static, str>,
+        offset: usize,
+        size: usize,
+        stride: usize,
+    },
+
+    /// The attribute offset is not a multiple of 4 or of its format size.
+    Misaligned {
+        layout: Cow<'static, str>,
+        attribute: Cow<'static, str>,
+        offset: usize,
+    },
+
+    /// Two attributes of the same layout share bytes.
+    Overlap {
+        layout: Cow<'static, str>,
+        first: Cow<'static, str>,
+        second: Cow<'static, str>,
+    },
+}
+
+impl fmt::Display for VertexLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingLocation { input, location } => write!(
+                f,
+                "Shader input {:?} at location {} is not provided by any vertex attribute",
+                input, location
+            ),
+            Self::IncompatibleFormat {
+                attribute,
+                location,
+                format,
+                expected,
+            } => write!(
+                f,
+                "Attribute {:?} at location {} has format {:?}, the shader expects {:?}",
+                attribute, location, format, expected
+            ),
+            Self::DuplicateLocation {
+                location,
+                first,
+                second,
+            } => write!(
+                f,
+                "Attributes {:?} and {:?} both use location {}",
+                first, second, location
+            ),
+            Self::OutOfBounds {
+                layout,
+                attribute,
+                offset,
+                size,
+                stride,
+            } => write!(
+                f,
+                "Attribute {:?} of layout {:?} spans bytes {}..{}, past the stride of {}",
+                attribute,
+                layout,
+                offset,
+                offset + size,
+                stride
+            ),
+            Self::Misaligned {
+                layout,
+                attribute,
+                offset,
+            } => write!(
+                f,
+                "Attribute {:?} of layout {:?} has misaligned offset {}",
+                attribute, layout, offset
+            ),
+            Self::Overlap {
+                layout,
+                first,
+                second,
+            } => write!(
+                f,
+                "Attributes {:?} and {:?} of layout {:?} overlap",
+                first, second, layout
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VertexLayoutError {}
@@ -0,0 +1,96 @@
+use super::VertexBufferLayout;
+
+/// Owned counterpart of `wgpu::VertexBufferLayout`.
+///
+/// The wgpu layout only borrows its attributes, this keeps the converted attributes
+/// alive so a borrowed layout can be handed to a pipeline descriptor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WgpuVertexBufferLayout {
+    pub array_stride: wgpu::BufferAddress,
+    pub step_mode: wgpu::VertexStepMode,
+    pub attributes: Vec<wgpu::VertexAttribute>,
+}
+
+impl WgpuVertexBufferLayout {
+    pub fn AsWgpu(&self) -> wgpu::VertexBufferLayout<'_> {
+        wgpu::VertexBufferLayout {
+            array_stride: self.array_stride,
+            step_mode: self.step_mode,
+            attributes: &self.attributes,
+        }
+    }
+}
+
+impl From<&VertexBufferLayout> for WgpuVertexBufferLayout {
+    fn from(layout: &VertexBufferLayout) -> Self {
+        Self {
+            array_stride: layout.stride as wgpu::BufferAddress,
+            step_mode: layout.step_mode.into(),
+            attributes: layout
+                .attributes
+                .iter()
+                .cloned()
+                .map(wgpu::VertexAttribute::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<VertexBufferLayout> for WgpuVertexBufferLayout {
+    fn from(layout: VertexBufferLayout) -> Self {
+        Self::from(&layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Render::{StepMode, VertexAttribute, VertexFormat};
+
+    #[test]
+    fn KeepsStrideStepModeAndAttributes() {
+        let layout = VertexBufferLayout {
+            label: "Instance".into(),
+            stride: 80,
+            step_mode: StepMode::Instance,
+            attributes: vec![
+                VertexAttribute {
+                    label: "Model".into(),
+                    format: VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 5,
+                },
+                VertexAttribute {
+                    label: "Color".into(),
+                    format: VertexFormat::UNorm8x4,
+                    offset: 64,
+                    shader_location: 9,
+                },
+            ],
+        };
+
+        let converted = WgpuVertexBufferLayout::from(&layout);
+
+        assert_eq!(converted.array_stride, 80);
+        assert_eq!(converted.step_mode, wgpu::VertexStepMode::Instance);
+        assert_eq!(
+            converted.attributes,
+            vec![
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 5,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Unorm8x4,
+                    offset: 64,
+                    shader_location: 9,
+                },
+            ]
+        );
+
+        let wgpu_layout = converted.AsWgpu();
+        assert_eq!(wgpu_layout.array_stride, 80);
+        assert_eq!(wgpu_layout.attributes, converted.attributes.as_slice());
+    }
+}
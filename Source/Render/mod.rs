@@ -1,42 +1,174 @@
-//#[path = "DrawModel.rs"]
-//mod _DrawModel;
-//pub use self::_DrawModel::*;
+#[path = "AddressMode.rs"]
+mod _AddressMode;
+pub use self::_AddressMode::*;
+
+#[path = "Backend.rs"]
+mod _Backend;
+pub use self::_Backend::*;
+
+#[path = "BindGroupLayout.rs"]
+mod _BindGroupLayout;
+pub use self::_BindGroupLayout::*;
+
+#[path = "BoundingBox.rs"]
+mod _BoundingBox;
+pub use self::_BoundingBox::*;
+
+#[path = "BlendMode.rs"]
+mod _BlendMode;
+pub use self::_BlendMode::*;
+
+#[path = "CompareFunction.rs"]
+mod _CompareFunction;
+pub use self::_CompareFunction::*;
+
+#[path = "CullMode.rs"]
+mod _CullMode;
+pub use self::_CullMode::*;
+
+#[path = "DepthStencilState.rs"]
+mod _DepthStencilState;
+pub use self::_DepthStencilState::*;
+
+#[path = "DrawModel.rs"]
+mod _DrawModel;
+pub use self::_DrawModel::*;
+
+#[path = "FilterMode.rs"]
+mod _FilterMode;
+pub use self::_FilterMode::*;
+
+#[path = "Frame.rs"]
+mod _Frame;
+pub use self::_Frame::*;
+
+#[path = "FrontFace.rs"]
+mod _FrontFace;
+pub use self::_FrontFace::*;
 
 #[path = "IndexFormat.rs"]
 mod _IndexFormat;
 pub use self::_IndexFormat::*;
 
-//#[path = "Instance.rs"]
-//mod _Instance;
-//pub use self::_Instance::*;
+#[path = "Instance.rs"]
+mod _Instance;
+pub use self::_Instance::*;
+
+#[path = "LimitsPreset.rs"]
+mod _LimitsPreset;
+pub use self::_LimitsPreset::*;
+
+#[path = "Material.rs"]
+mod _Material;
+pub use self::_Material::*;
+
+#[path = "MipmapGenerator.rs"]
+mod _MipmapGenerator;
+pub use self::_MipmapGenerator::*;
+
+#[path = "MipmapGeneratorCache.rs"]
+mod _MipmapGeneratorCache;
+pub use self::_MipmapGeneratorCache::*;
+
+#[path = "MipmapMode.rs"]
+mod _MipmapMode;
+pub use self::_MipmapMode::*;
+
+#[path = "Model.rs"]
+mod _Model;
+pub use self::_Model::*;
+
+#[path = "ModelNode.rs"]
+mod _ModelNode;
+pub use self::_ModelNode::*;
 
-//#[path = "Material.rs"]
-//mod _Material;
-//pub use self::_Material::*;
+#[path = "ModelOptions.rs"]
+mod _ModelOptions;
+pub use self::_ModelOptions::*;
 
-//#[path = "Model.rs"]
-//mod _Model;
-//pub use self::_Model::*;
+#[path = "MultisampleState.rs"]
+mod _MultisampleState;
+pub use self::_MultisampleState::*;
+
+#[path = "NormalMode.rs"]
+mod _NormalMode;
+pub use self::_NormalMode::*;
+
+#[path = "PipelineCache.rs"]
+mod _PipelineCache;
+pub use self::_PipelineCache::*;
+
+#[path = "PipelineCacheStats.rs"]
+mod _PipelineCacheStats;
+pub use self::_PipelineCacheStats::*;
+
+#[path = "PipelineDescriptor.rs"]
+mod _PipelineDescriptor;
+pub use self::_PipelineDescriptor::*;
+
+#[path = "PipelineHandle.rs"]
+mod _PipelineHandle;
+pub use self::_PipelineHandle::*;
 
 #[path = "PolygonMode.rs"]
 mod _PolygonMode;
 pub use self::_PolygonMode::*;
 
+#[path = "PowerPreference.rs"]
+mod _PowerPreference;
+pub use self::_PowerPreference::*;
+
+#[path = "PresentMode.rs"]
+mod _PresentMode;
+pub use self::_PresentMode::*;
+
+#[path = "Primitive.rs"]
+mod _Primitive;
+pub use self::_Primitive::*;
+
+#[path = "PrimitiveTopology.rs"]
+mod _PrimitiveTopology;
+pub use self::_PrimitiveTopology::*;
+
+#[path = "ReflectedLayouts.rs"]
+mod _ReflectedLayouts;
+pub use self::_ReflectedLayouts::*;
+
 #[path = "Renderer.rs"]
 mod _Renderer;
 pub use self::_Renderer::*;
 
+#[path = "RendererConfig.rs"]
+mod _RendererConfig;
+pub use self::_RendererConfig::*;
+
+#[path = "RendererError.rs"]
+mod _RendererError;
+pub use self::_RendererError::*;
+
+#[path = "RenderTarget.rs"]
+mod _RenderTarget;
+pub use self::_RenderTarget::*;
+
 #[path = "StepMode.rs"]
 mod _StepMode;
 pub use self::_StepMode::*;
 
-//#[path = "Mesh.rs"]
-//mod _Mesh;
-//pub use self::_Mesh::*;
+#[path = "Mesh.rs"]
+mod _Mesh;
+pub use self::_Mesh::*;
 
-//#[path = "Texture.rs"]
-//mod _Texture;
-//pub use self::_Texture::*;
+#[path = "MeshData.rs"]
+mod _MeshData;
+pub use self::_MeshData::*;
+
+#[path = "Texture.rs"]
+mod _Texture;
+pub use self::_Texture::*;
+
+#[path = "TextureOptions.rs"]
+mod _TextureOptions;
+pub use self::_TextureOptions::*;
 
 #[path = "Vertex.rs"]
 mod _Vertex;
@@ -54,10 +186,18 @@ pub use self::_VertexBufferLayout::*;
 mod _VertexFormat;
 pub use self::_VertexFormat::*;
 
+#[path = "VertexLayoutError.rs"]
+mod _VertexLayoutError;
+pub use self::_VertexLayoutError::*;
+
 #[path = "VertexBuffer.rs"]
 mod _VertexBuffer;
 pub use self::_VertexBuffer::*;
 
+#[path = "WgpuVertexBufferLayout.rs"]
+mod _WgpuVertexBufferLayout;
+pub use self::_WgpuVertexBufferLayout::*;
+
 #[path = "IndexBuffer.rs"]
 mod _IndexBuffer;
 pub use self::_IndexBuffer::*;
@@ -1,7 +1,8 @@
-use crate::Render::Renderer;
-use crate::State;
+use crate::Render::{Renderer, RendererConfig};
+use crate::Shader::ShaderWatcher;
+use crate::{ErrorAction, FrameLimiter, RuntimeConfig, State};
 use anyhow::Result;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use winit::dpi::LogicalSize;
 use winit::event::*;
 use winit::event_loop::{ControlFlow, EventLoop};
@@ -12,76 +13,178 @@ use winit::window::{Fullscreen, WindowBuilder};
 pub struct Runtime;
 
 impl Runtime {
+    /// Runs the state with the default configuration, renderer settings are read from the environment.
     pub fn Execute<T: State>() -> Result<()> {
+        Self::ExecuteWith::<T>(RuntimeConfig {
+            renderer: RendererConfig::FromEnv(),
+            ..Default::default()
+        })
+    }
+
+    pub fn ExecuteWith<T: State>(config: RuntimeConfig) -> Result<()> {
         env_logger::init();
 
         let event_loop = EventLoop::new();
 
-        //let monitor = event_loop.available_monitors().nth(0);
+        let fullscreen = config.window_mode.ToFullscreen(
+            event_loop.available_monitors(),
+            event_loop.primary_monitor(),
+        );
+
+        let mut builder = WindowBuilder::new()
+            .with_title(&config.title)
+            .with_inner_size(LogicalSize::new(config.width, config.height))
+            .with_resizable(config.resizable)
+            .with_fullscreen(fullscreen);
+
+        if let Some((min_width, min_height)) = config.min_size {
+            builder = builder.with_min_inner_size(LogicalSize::new(min_width, min_height));
+        }
 
-        let window = WindowBuilder::new()
-            .with_title("Graphics")
-            //.with_fullscreen(Some(Fullscreen::Borderless(monitor)))
-            .with_inner_size(LogicalSize::new(1280, 720))
-            .build(&event_loop)?;
+        let window = builder.build(&event_loop)?;
+        let window_id = window.id();
 
-        let mut renderer = pollster::block_on(Renderer::New(window))?;
+        let mut renderer =
+            pollster::block_on(Renderer::New(window, &config.renderer, config.present_mode))?;
 
         let mut app = T::Init(&renderer)?;
 
+        let mut frame_limiter = config.target_fps.map(FrameLimiter::New);
+
+        let mut shader_watcher = config
+            .hot_reload
+            .then(|| ShaderWatcher::New(Duration::from_millis(250)));
+
         let mut last_update = Instant::now();
+        let mut fixed_timestep = config.fixed_timestep;
 
         let mut is_resumed = true;
         let mut is_focused = true;
         let mut is_redraw_requested = true;
 
         event_loop.run(move |event, _, control_flow| {
-            *control_flow = if is_resumed && is_focused {
-                ControlFlow::Poll
-            } else {
+            *control_flow = if !is_resumed || !is_focused {
                 ControlFlow::Wait
+            } else if let Some(frame_limiter) = &frame_limiter {
+                // Sleep in the event loop instead of polling until the next frame is due
+                ControlFlow::WaitUntil(frame_limiter.GetWakeTime())
+            } else {
+                ControlFlow::Poll
             };
 
             match event {
                 Event::Resumed => is_resumed = true,
                 Event::Suspended => is_resumed = false,
-                Event::RedrawRequested(window_id) => {
-                    if window_id == renderer.window.id() {
+                Event::RedrawRequested(id) => {
+                    if id == window_id {
+                        if let Some(frame_limiter) = &mut frame_limiter {
+                            frame_limiter.Wait();
+                        }
+
                         let now = Instant::now();
                         let delta = now - last_update;
                         last_update = now;
 
-                        app.Update(&renderer, delta);
+                        renderer.frame_stats.Record(delta);
 
-                        match app.Draw(&mut renderer) {
-                            Ok(_) => {}
-                            // Reconfigure the surface if lost
-                            Err(wgpu::SurfaceError::Lost) => {
-                                let size = renderer.window.inner_size();
-                                renderer.Resize(size.width, size.height);
-                                app.Resize(&renderer);
+                        let mut alpha = 1.0;
+
+                        if let Some(fixed_timestep) = &mut fixed_timestep {
+                            let (steps, step_alpha) = fixed_timestep.Advance(delta);
+
+                            for _ in 0..steps {
+                                app.FixedUpdate(&renderer, fixed_timestep.GetStep());
+                            }
+
+                            alpha = step_alpha;
+                        }
+
+                        // Swapped pipelines are picked up by the draw of this frame
+                        if let Some(shader_watcher) = &mut shader_watcher {
+                            let files = renderer.pipeline_cache.GetWatchedFiles();
+                            let changed = shader_watcher.Poll(&files);
+
+                            if !changed.is_empty() {
+                                renderer.ReloadShaders(&changed);
+                                app.ReloadShaders(&renderer, &changed);
                             }
-                            // The system is out of memory, we should probably quit
-                            Err(wgpu::SurfaceError::OutOfMemory) => {
-                                *control_flow = ControlFlow::Exit
+                        }
+
+                        app.Update(&renderer, delta);
+
+                        let result = app.DrawInterpolated(&mut renderer, alpha);
+
+                        let errors = result
+                            .err()
+                            .into_iter()
+                            .chain(renderer.TakeErrors())
+                            .collect::<Vec<_>>();
+
+                        for error in errors {
+                            match app.OnError(&mut renderer, &error) {
+                                ErrorAction::Retry => log::warn!("{}", error),
+                                ErrorAction::Reconfigure => {
+                                    log::warn!("{}, reconfiguring surface", error);
+
+                                    if let Some(size) =
+                                        renderer.window.as_ref().map(|w| w.inner_size())
+                                    {
+                                        renderer.Resize(size.width, size.height);
+                                    }
+
+                                    app.Resize(&renderer);
+                                }
+                                ErrorAction::RecreateDevice => {
+                                    log::warn!("{}, recreating device", error);
+
+                                    let recreated = pollster::block_on(renderer.RecreateDevice())
+                                        .map_err(anyhow::Error::from)
+                                        .and_then(|_| T::Init(&renderer));
+
+                                    match recreated {
+                                        Ok(state) => app = state,
+                                        Err(e) => {
+                                            log::error!("Failed to recreate device: {:?}", e);
+                                            *control_flow = ControlFlow::Exit;
+                                        }
+                                    }
+
+                                    break;
+                                }
+                                ErrorAction::Exit => {
+                                    log::error!("{}", error);
+                                    *control_flow = ControlFlow::Exit;
+                                    break;
+                                }
                             }
-                            // All other errors (Outdated, Timeout) should be resolved by the next frame
-                            Err(e) => eprintln!("{:?}", e),
                         }
 
                         is_redraw_requested = false;
                     }
                 }
                 Event::MainEventsCleared => {
-                    if is_focused && is_resumed && !is_redraw_requested {
-                        renderer.window.request_redraw();
-                        is_redraw_requested = true;
+                    if is_focused && is_resumed {
+                        let is_due = frame_limiter.as_ref().is_none_or(FrameLimiter::IsDue);
+
+                        if is_due && !is_redraw_requested {
+                            if let Some(window) = &renderer.window {
+                                window.request_redraw();
+                            }
+                            is_redraw_requested = true;
+                        }
                     } else {
                         // Freeze time while the app is not in the foreground
                         last_update = Instant::now();
+
+                        if let Some(frame_limiter) = &mut frame_limiter {
+                            frame_limiter.Reset();
+                        }
                     }
                 }
-                Event::WindowEvent { event, window_id } if window_id == renderer.window.id() => {
+                Event::WindowEvent {
+                    event,
+                    window_id: id,
+                } if id == window_id => {
                     if !app.Input(&renderer, &event) {
                         match event {
                             WindowEvent::CloseRequested
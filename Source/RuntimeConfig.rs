@@ -0,0 +1,51 @@
+use crate::Render::{PresentMode, RendererConfig};
+use crate::{FixedTimestep, WindowMode};
+use serde::{Deserialize, Serialize};
+
+/// Window and presentation settings used by [`Runtime::ExecuteWith`](crate::Runtime::ExecuteWith).
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    pub title: String,
+
+    /// Initial inner size in logical pixels.
+    pub width: u32,
+    pub height: u32,
+
+    /// Minimum inner size in logical pixels.
+    pub min_size: Option<(u32, u32)>,
+
+    pub resizable: bool,
+    pub window_mode: WindowMode,
+    pub present_mode: PresentMode,
+
+    /// Runs fixed updates at a constant rate when set, in addition to the variable update.
+    pub fixed_timestep: Option<FixedTimestep>,
+
+    /// Caps the frame rate, `None` renders as fast as the present mode allows.
+    pub target_fps: Option<u32>,
+
+    /// Rebuilds pipelines when the shader files they were loaded from change,
+    /// enabled by default in debug builds.
+    pub hot_reload: bool,
+
+    pub renderer: RendererConfig,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            title: "Graphics".into(),
+            width: 1280,
+            height: 720,
+            min_size: None,
+            resizable: true,
+            window_mode: WindowMode::default(),
+            present_mode: PresentMode::default(),
+            fixed_timestep: None,
+            target_fps: None,
+            hot_reload: cfg!(debug_assertions),
+            renderer: RendererConfig::default(),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use super::{ShaderStage, VertexInput};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntryPointReflection {
+    pub name: String,
+    pub stage: ShaderStage,
+
+    /// `@location` inputs, only filled for vertex entry points.
+    pub vertex_inputs: Vec<VertexInput>,
+
+    /// Workgroup size of compute entry points, zero otherwise.
+    pub workgroup_size: [u32; 3],
+}
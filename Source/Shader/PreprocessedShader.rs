@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Output of the [`ShaderPreprocessor`](super::ShaderPreprocessor).
+#[derive(Clone, Debug)]
+pub struct PreprocessedShader {
+    pub code: String,
+
+    /// Files read to produce the code, the shader file itself and its includes.
+    pub files: Vec<PathBuf>,
+
+    /// File and line each output line came from.
+    lines: Vec<(Arc<str>, u32)>,
+}
+
+impl PreprocessedShader {
+    pub(crate) fn New(code: String, lines: Vec<(Arc<str>, u32)>, files: Vec<PathBuf>) -> Self {
+        Self { code, files, lines }
+    }
+
+    /// Maps a 1-based line of `code` back to its file and line.
+    pub fn GetSourceLine(&self, line: u32) -> Option<(&str, u32)> {
+        let (file, line) = self.lines.get(line.checked_sub(1)? as usize)?;
+        Some((file, *line))
+    }
+
+    /// Formats a location in `code` as `file:line:column`.
+    pub(crate) fn FormatLocation(&self, location: &naga::SourceLocation) -> String {
+        match self.GetSourceLine(location.line_number) {
+            Some((file, line)) => format!("{}:{}:{}", file, line, location.line_position),
+            None => format!("{}:{}", location.line_number, location.line_position),
+        }
+    }
+}
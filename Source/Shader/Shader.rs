@@ -1,12 +1,31 @@
-use super::{ShaderSource, ShaderStage};
+use super::{
+    EntryPointReflection, PreprocessedShader, ShaderBinding, ShaderDefines, ShaderPreprocessor,
+    ShaderReflection, ShaderSource, ShaderStage, VertexInput,
+};
+use crate::Render::{RendererError, VertexFormat};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Shader {
     pub label: Cow<'static, str>,
     pub source: ShaderSource,
     pub stage: ShaderStage,
+
+    /// Definitions for the preprocessor, passed to naga for GLSL sources.
+    #[serde(default)]
+    pub defines: ShaderDefines,
+
+    /// Directories searched by WGSL `#include` directives.
+    #[serde(default)]
+    pub include_paths: Vec<PathBuf>,
+
+    /// File the source was loaded from, used to reload it and resolve relative includes.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
 }
 
 impl Shader {
@@ -15,24 +34,31 @@ impl Shader {
             label: "".into(),
             source,
             stage,
+            defines: ShaderDefines::default(),
+            include_paths: Vec::new(),
+            path: None,
         }
     }
 
-    /*
-    pub fn FromSpirv(spirv: &[u8]) -> Self {
+    pub fn FromSpirv(stage: ShaderStage, spirv: &[u8]) -> Self {
         Self {
             label: "".into(),
-            source: ShaderSource::Spirv(spirv),
+            source: ShaderSource::SpirvFromBytes(spirv),
             stage,
+            defines: ShaderDefines::default(),
+            include_paths: Vec::new(),
+            path: None,
         }
     }
-    */
 
     pub fn FromGlsl(stage: ShaderStage, glsl: &str) -> Self {
         Self {
             label: "".into(),
             source: ShaderSource::Glsl(glsl.to_string()),
             stage,
+            defines: ShaderDefines::default(),
+            include_paths: Vec::new(),
+            path: None,
         }
     }
 
@@ -41,6 +67,587 @@ impl Shader {
             label: "".into(),
             source: ShaderSource::Wgsl(wgsl.to_string()),
             stage: ShaderStage::Multiple,
+            defines: ShaderDefines::default(),
+            include_paths: Vec::new(),
+            path: None,
+        }
+    }
+
+    /// Loads a shader from disk, the kind of source is picked from the extension.
+    ///
+    /// `.wgsl` files are WGSL, `.spv` files SPIR-V and `.vert`, `.frag` and `.comp`
+    /// files GLSL for the matching stage.
+    pub fn FromFile<P: AsRef<Path>>(path: P) -> Result<Self, RendererError> {
+        let path = path.as_ref();
+
+        let stage = match path.extension().and_then(|e| e.to_str()) {
+            Some("vert") => ShaderStage::Vertex,
+            Some("frag") => ShaderStage::Fragment,
+            Some("comp") => ShaderStage::Compute,
+            _ => ShaderStage::Multiple,
+        };
+
+        let mut shader = Self::New(stage, ShaderSource::Wgsl(String::new()));
+        shader.label = path.display().to_string().into();
+        // Absolute so reloads and includes don't depend on the working directory later on
+        shader.path = Some(std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()));
+        shader.Reload()?;
+
+        Ok(shader)
+    }
+
+    /// Reads the source again from [`Shader::path`], no-op for shaders created from memory.
+    pub fn Reload(&mut self) -> Result<(), RendererError> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let load_error = |e: std::io::Error| RendererError::AssetLoad {
+            path: path.clone(),
+            message: e.to_string(),
+        };
+
+        self.source = match path.extension().and_then(|e| e.to_str()) {
+            Some("spv") => ShaderSource::Spirv(fs::read(path).map_err(load_error)?),
+            Some("vert" | "frag" | "comp") => {
+                ShaderSource::Glsl(fs::read_to_string(path).map_err(load_error)?)
+            }
+            _ => ShaderSource::Wgsl(fs::read_to_string(path).map_err(load_error)?),
+        };
+
+        Ok(())
+    }
+
+    /// Files the shader is built from, empty for shaders created from memory.
+    pub fn GetFiles(&self) -> Vec<PathBuf> {
+        // Fall back to the main file when the includes cannot be resolved right now
+        self.Preprocess()
+            .map(|preprocessed| preprocessed.files)
+            .unwrap_or_else(|_| self.path.iter().cloned().collect())
+    }
+
+    /// Runs the preprocessor on WGSL sources, other sources are returned unchanged.
+    pub fn Preprocess(&self) -> Result<PreprocessedShader, RendererError> {
+        let name = self.GetName();
+
+        match &self.source {
+            ShaderSource::Wgsl(wgsl) => {
+                ShaderPreprocessor::New(self.include_paths.clone(), self.defines.clone())
+                    .Process(&name, self.path.as_deref(), wgsl)
+                    .map_err(|e| self.CompilationError(e))
+            }
+            ShaderSource::Glsl(glsl) => {
+                let lines = (1..=glsl.lines().count() as u32)
+                    .map(|line| (name.clone(), line))
+                    .collect();
+
+                Ok(PreprocessedShader::New(
+                    glsl.clone(),
+                    lines,
+                    self.path.iter().cloned().collect(),
+                ))
+            }
+            ShaderSource::Spirv(_) => Ok(PreprocessedShader::New(
+                String::new(),
+                Vec::new(),
+                self.path.iter().cloned().collect(),
+            )),
+        }
+    }
+
+    /// Parses the source into a naga module.
+    pub fn Parse(&self) -> Result<naga::Module, RendererError> {
+        self.ParseSource().map(|(module, _)| module)
+    }
+
+    fn ParseSource(&self) -> Result<(naga::Module, PreprocessedShader), RendererError> {
+        let source = self.Preprocess()?;
+
+        let module = match &self.source {
+            ShaderSource::Wgsl(_) => naga::front::wgsl::parse_str(&source.code).map_err(|e| {
+                let message = match e.location(&source.code) {
+                    Some(location) => {
+                        format!("{}: {}", source.FormatLocation(&location), e.message())
+                    }
+                    None => e.message().to_string(),
+                };
+
+                self.CompilationError(message)
+            })?,
+            ShaderSource::Glsl(glsl) => {
+                let stage = match self.stage {
+                    ShaderStage::Vertex => naga::ShaderStage::Vertex,
+                    ShaderStage::Fragment => naga::ShaderStage::Fragment,
+                    ShaderStage::Compute => naga::ShaderStage::Compute,
+                    ShaderStage::Multiple => {
+                        return Err(
+                            self.CompilationError("GLSL shaders must target a single stage".into())
+                        )
+                    }
+                };
+
+                let options = naga::front::glsl::Options {
+                    stage,
+                    defines: self
+                        .defines
+                        .Iter()
+                        .map(|(name, value)| (name.to_string(), value.to_string()))
+                        .collect(),
+                };
+
+                naga::front::glsl::Parser::default()
+                    .parse(&options, glsl)
+                    .map_err(|errors| {
+                        let messages = errors
+                            .iter()
+                            .map(|e| match e.meta.is_defined() {
+                                true => format!(
+                                    "{}: {}",
+                                    source.FormatLocation(&e.meta.location(glsl)),
+                                    e.kind
+                                ),
+                                false => e.kind.to_string(),
+                            })
+                            .collect::<Vec<_>>();
+
+                        self.CompilationError(messages.join("\n"))
+                    })?
+            }
+            ShaderSource::Spirv(spirv) => {
+                naga::front::spv::parse_u8_slice(spirv, &Default::default())
+                    .map_err(|e| self.CompilationError(e.to_string()))?
+            }
+        };
+
+        Ok((module, source))
+    }
+
+    /// Translates the shader to validated SPIR-V words for wgpu.
+    pub fn CompileSpirv(&self) -> Result<Vec<u32>, RendererError> {
+        let (module, info) = self.Validate()?;
+
+        naga::back::spv::write_vec(&module, &info, &Default::default(), None)
+            .map_err(|e| self.CompilationError(e.to_string()))
+    }
+
+    /// Lists the entry points and resource bindings declared by the shader.
+    pub fn Reflect(&self) -> Result<ShaderReflection, RendererError> {
+        let (module, info) = self.Validate()?;
+
+        let mut entry_points = Vec::new();
+        let mut bindings = Vec::<ShaderBinding>::new();
+
+        for (index, entry_point) in module.entry_points.iter().enumerate() {
+            let stage = ShaderStage::from(entry_point.stage);
+
+            let vertex_inputs = match stage {
+                ShaderStage::Vertex => self.CollectVertexInputs(&module, &entry_point.function)?,
+                _ => Vec::new(),
+            };
+
+            entry_points.push(EntryPointReflection {
+                name: entry_point.name.clone(),
+                stage,
+                vertex_inputs,
+                workgroup_size: entry_point.workgroup_size,
+            });
+
+            let function_info = info.get_entry_point(index);
+
+            for (handle, global) in module.global_variables.iter() {
+                let resource = match &global.binding {
+                    Some(resource) if !function_info[handle].is_empty() => resource,
+                    _ => continue,
+                };
+
+                let existing = bindings.iter_mut().find(|binding| {
+                    binding.group == resource.group && binding.binding == resource.binding
+                });
+
+                match existing {
+                    Some(binding) => binding.visibility |= stage.into(),
+                    None => bindings.push(ShaderBinding {
+                        name: global.name.clone().unwrap_or_default(),
+                        group: resource.group,
+                        binding: resource.binding,
+                        visibility: stage.into(),
+                        ty: self.GetBindingType(&module, global)?,
+                    }),
+                }
+            }
         }
+
+        bindings.sort_by_key(|binding| (binding.group, binding.binding));
+
+        Ok(ShaderReflection {
+            entry_points,
+            bindings,
+        })
+    }
+
+    /// Parses and validates the shader with naga.
+    pub(crate) fn Validate(
+        &self,
+    ) -> Result<(naga::Module, naga::valid::ModuleInfo), RendererError> {
+        let (module, source) = self.ParseSource()?;
+
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|e| {
+            let message = match e.location(&source.code) {
+                Some(location) => format!("{}: {}", source.FormatLocation(&location), e.as_inner()),
+                None => e.as_inner().to_string(),
+            };
+
+            self.CompilationError(message)
+        })?;
+
+        Ok((module, info))
+    }
+
+    fn GetBindingType(
+        &self,
+        module: &naga::Module,
+        global: &naga::GlobalVariable,
+    ) -> Result<wgpu::BindingType, RendererError> {
+        let ty = &module.types[global.ty].inner;
+        let size = wgpu::BufferSize::new(ty.size(&module.constants) as u64);
+
+        let binding_type = match (global.space, ty) {
+            (naga::AddressSpace::Uniform, _) => wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: size,
+            },
+            (naga::AddressSpace::Storage { access }, _) => wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage {
+                    read_only: !access.contains(naga::StorageAccess::STORE),
+                },
+                has_dynamic_offset: false,
+                min_binding_size: size,
+            },
+            (naga::AddressSpace::Handle, naga::TypeInner::Sampler { comparison }) => {
+                wgpu::BindingType::Sampler(if *comparison {
+                    wgpu::SamplerBindingType::Comparison
+                } else {
+                    wgpu::SamplerBindingType::Filtering
+                })
+            }
+            (
+                naga::AddressSpace::Handle,
+                naga::TypeInner::Image {
+                    dim,
+                    arrayed,
+                    class,
+                },
+            ) => {
+                let view_dimension = match (dim, arrayed) {
+                    (naga::ImageDimension::D1, _) => wgpu::TextureViewDimension::D1,
+                    (naga::ImageDimension::D2, false) => wgpu::TextureViewDimension::D2,
+                    (naga::ImageDimension::D2, true) => wgpu::TextureViewDimension::D2Array,
+                    (naga::ImageDimension::D3, _) => wgpu::TextureViewDimension::D3,
+                    (naga::ImageDimension::Cube, false) => wgpu::TextureViewDimension::Cube,
+                    (naga::ImageDimension::Cube, true) => wgpu::TextureViewDimension::CubeArray,
+                };
+
+                match class {
+                    // The shader can't tell whether the texture will be filtered, assume it is
+                    naga::ImageClass::Sampled { kind, multi } => wgpu::BindingType::Texture {
+                        sample_type: match kind {
+                            naga::ScalarKind::Sint => wgpu::TextureSampleType::Sint,
+                            naga::ScalarKind::Uint => wgpu::TextureSampleType::Uint,
+                            _ => wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        view_dimension,
+                        multisampled: *multi,
+                    },
+                    naga::ImageClass::Depth { multi } => wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension,
+                        multisampled: *multi,
+                    },
+                    naga::ImageClass::Storage { format, access } => {
+                        wgpu::BindingType::StorageTexture {
+                            access: if !access.contains(naga::StorageAccess::STORE) {
+                                wgpu::StorageTextureAccess::ReadOnly
+                            } else if !access.contains(naga::StorageAccess::LOAD) {
+                                wgpu::StorageTextureAccess::WriteOnly
+                            } else {
+                                wgpu::StorageTextureAccess::ReadWrite
+                            },
+                            format: GetStorageFormat(*format),
+                            view_dimension,
+                        }
+                    }
+                }
+            }
+            _ => {
+                return Err(self.CompilationError(format!(
+                    "Binding {:?} has an unsupported type",
+                    global.name.as_deref().unwrap_or_default()
+                )))
+            }
+        };
+
+        Ok(binding_type)
+    }
+
+    /// Returns the `@location` inputs of a vertex entry point, sorted by location.
+    pub fn GetVertexInputs(&self, entry_point: &str) -> Result<Vec<VertexInput>, RendererError> {
+        let module = self.Parse()?;
+
+        let function = module
+            .entry_points
+            .iter()
+            .find(|ep| ep.stage == naga::ShaderStage::Vertex && ep.name == entry_point)
+            .map(|ep| &ep.function)
+            .ok_or_else(|| {
+                self.CompilationError(format!("No vertex entry point named {:?}", entry_point))
+            })?;
+
+        self.CollectVertexInputs(&module, function)
+    }
+
+    fn CollectVertexInputs(
+        &self,
+        module: &naga::Module,
+        function: &naga::Function,
+    ) -> Result<Vec<VertexInput>, RendererError> {
+        let mut inputs = Vec::new();
+
+        for argument in &function.arguments {
+            let name = argument.name.clone().unwrap_or_default();
+
+            match (&argument.binding, &module.types[argument.ty].inner) {
+                (Some(binding), _) => {
+                    self.PushVertexInput(module, &mut inputs, name, binding, argument.ty)?
+                }
+                // Inputs grouped in a struct carry the bindings on the members
+                (None, naga::TypeInner::Struct { members, .. }) => {
+                    for member in members {
+                        if let Some(binding) = &member.binding {
+                            let name = member.name.clone().unwrap_or_default();
+                            self.PushVertexInput(module, &mut inputs, name, binding, member.ty)?;
+                        }
+                    }
+                }
+                (None, _) => {}
+            }
+        }
+
+        inputs.sort_by_key(|input| input.location);
+
+        Ok(inputs)
+    }
+
+    fn PushVertexInput(
+        &self,
+        module: &naga::Module,
+        inputs: &mut Vec<VertexInput>,
+        name: String,
+        binding: &naga::Binding,
+        ty: naga::Handle<naga::Type>,
+    ) -> Result<(), RendererError> {
+        let location = match binding {
+            naga::Binding::Location { location, .. } => *location,
+            naga::Binding::BuiltIn(_) => return Ok(()),
+        };
+
+        let format = match module.types[ty].inner {
+            naga::TypeInner::Scalar { kind, width } => VertexFormat::FromShaderType(kind, width, 1),
+            naga::TypeInner::Vector { size, kind, width } => {
+                VertexFormat::FromShaderType(kind, width, size as u32)
+            }
+            _ => None,
+        };
+
+        let format = format.ok_or_else(|| {
+            self.CompilationError(format!(
+                "Vertex input {:?} at location {} has an unsupported type",
+                name, location
+            ))
+        })?;
+
+        inputs.push(VertexInput {
+            name,
+            location,
+            format,
+        });
+
+        Ok(())
+    }
+
+    /// Name used for the shader in error messages.
+    fn GetName(&self) -> Arc<str> {
+        match self.label.as_ref() {
+            "" => "shader".into(),
+            label => label.into(),
+        }
+    }
+
+    fn CompilationError(&self, message: String) -> RendererError {
+        RendererError::ShaderCompilation {
+            label: self.label.to_string(),
+            message,
+        }
+    }
+}
+
+fn GetStorageFormat(format: naga::StorageFormat) -> wgpu::TextureFormat {
+    use naga::StorageFormat as Sf;
+    use wgpu::TextureFormat as Tf;
+
+    match format {
+        Sf::R8Unorm => Tf::R8Unorm,
+        Sf::R8Snorm => Tf::R8Snorm,
+        Sf::R8Uint => Tf::R8Uint,
+        Sf::R8Sint => Tf::R8Sint,
+        Sf::R16Uint => Tf::R16Uint,
+        Sf::R16Sint => Tf::R16Sint,
+        Sf::R16Float => Tf::R16Float,
+        Sf::Rg8Unorm => Tf::Rg8Unorm,
+        Sf::Rg8Snorm => Tf::Rg8Snorm,
+        Sf::Rg8Uint => Tf::Rg8Uint,
+        Sf::Rg8Sint => Tf::Rg8Sint,
+        Sf::R32Uint => Tf::R32Uint,
+        Sf::R32Sint => Tf::R32Sint,
+        Sf::R32Float => Tf::R32Float,
+        Sf::Rg16Uint => Tf::Rg16Uint,
+        Sf::Rg16Sint => Tf::Rg16Sint,
+        Sf::Rg16Float => Tf::Rg16Float,
+        Sf::Rgba8Unorm => Tf::Rgba8Unorm,
+        Sf::Rgba8Snorm => Tf::Rgba8Snorm,
+        Sf::Rgba8Uint => Tf::Rgba8Uint,
+        Sf::Rgba8Sint => Tf::Rgba8Sint,
+        Sf::Rgb10a2Unorm => Tf::Rgb10a2Unorm,
+        Sf::Rg11b10Float => Tf::Rg11b10Float,
+        Sf::Rg32Uint => Tf::Rg32Uint,
+        Sf::Rg32Sint => Tf::Rg32Sint,
+        Sf::Rg32Float => Tf::Rg32Float,
+        Sf::Rgba16Uint => Tf::Rgba16Uint,
+        Sf::Rgba16Sint => Tf::Rgba16Sint,
+        Sf::Rgba16Float => Tf::Rgba16Float,
+        Sf::Rgba32Uint => Tf::Rgba32Uint,
+        Sf::Rgba32Sint => Tf::Rgba32Sint,
+        Sf::Rgba32Float => Tf::Rgba32Float,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn LoadCubes() -> Shader {
+        Shader::FromFile(Path::new(env!("CARGO_MANIFEST_DIR")).join("Shaders/Cubes.wgsl")).unwrap()
+    }
+
+    fn Input(name: &str, location: u32, format: VertexFormat) -> VertexInput {
+        VertexInput {
+            name: name.to_string(),
+            location,
+            format,
+        }
+    }
+
+    #[test]
+    fn ReflectsCubesBindings() {
+        let reflection = LoadCubes().Reflect().unwrap();
+
+        assert_eq!(reflection.GetGroupCount(), 2);
+        assert_eq!(
+            reflection.bindings,
+            vec![
+                ShaderBinding {
+                    name: "t_diffuse".into(),
+                    group: 0,
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                },
+                ShaderBinding {
+                    name: "s_diffuse".into(),
+                    group: 0,
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                },
+                ShaderBinding {
+                    name: "camera".into(),
+                    group: 1,
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(64),
+                    },
+                },
+            ]
+        );
+
+        assert_eq!(reflection.GetBindGroupLayoutEntries(0).len(), 2);
+        assert_eq!(reflection.GetBindGroupLayoutEntries(1).len(), 1);
+    }
+
+    #[test]
+    fn ReflectsCubesVertexInputs() {
+        let reflection = LoadCubes().Reflect().unwrap();
+
+        let vertex = reflection
+            .GetEntryPoint(ShaderStage::Vertex, "vs_main")
+            .unwrap();
+
+        assert_eq!(
+            vertex.vertex_inputs,
+            vec![
+                Input("position", 0, VertexFormat::Float32x3),
+                Input("tex_coords", 1, VertexFormat::Float32x2),
+                Input("model_matrix_0", 5, VertexFormat::Float32x4),
+                Input("model_matrix_1", 6, VertexFormat::Float32x4),
+                Input("model_matrix_2", 7, VertexFormat::Float32x4),
+                Input("model_matrix_3", 8, VertexFormat::Float32x4),
+            ]
+        );
+
+        let fragment = reflection
+            .GetEntryPoint(ShaderStage::Fragment, "fs_main")
+            .unwrap();
+
+        assert!(fragment.vertex_inputs.is_empty());
+        assert!(reflection
+            .GetEntryPoint(ShaderStage::Vertex, "fs_main")
+            .is_none());
+    }
+
+    #[test]
+    fn CompilesShippedShadersToSpirv() {
+        for name in ["Cubes.wgsl", "Triangle.wgsl"] {
+            let shader = Shader::FromFile(
+                Path::new(env!("CARGO_MANIFEST_DIR"))
+                    .join("Shaders")
+                    .join(name),
+            )
+            .unwrap();
+
+            let spirv = shader.CompileSpirv().unwrap();
+            assert_eq!(spirv[0], 0x0723_0203, "{} is not SPIR-V", name);
+        }
+    }
+
+    #[test]
+    fn RejectsOutdatedStageAttributes() {
+        let shader = Shader::FromWgsl(
+            "@stage(vertex) fn vs_main() -> @builtin(position) vec4<f32> { return vec4<f32>(0.0); }",
+        );
+
+        assert!(shader.Validate().is_err());
     }
 }
@@ -0,0 +1,24 @@
+/// Resource bound at `@group(group) @binding(binding)`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShaderBinding {
+    pub name: String,
+    pub group: u32,
+    pub binding: u32,
+
+    /// Stages of the entry points that use the resource.
+    pub visibility: wgpu::ShaderStages,
+
+    /// Buffer sizes come from the shader types, sampled float textures are assumed filterable.
+    pub ty: wgpu::BindingType,
+}
+
+impl ShaderBinding {
+    pub fn GetLayoutEntry(&self) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: self.binding,
+            visibility: self.visibility,
+            ty: self.ty,
+            count: None,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Preprocessor definitions of a shader, part of its cache key.
+///
+/// Names without a value are only visible to `#ifdef`, names with a value are
+/// also substituted in the source.
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ShaderDefines(BTreeMap<String, String>);
+
+impl ShaderDefines {
+    pub fn New() -> Self {
+        Self::default()
+    }
+
+    /// Defines `name` without a value.
+    pub fn Define(&mut self, name: impl Into<String>) {
+        self.0.insert(name.into(), String::new());
+    }
+
+    pub fn Set(&mut self, name: impl Into<String>, value: impl ToString) {
+        self.0.insert(name.into(), value.to_string());
+    }
+
+    pub fn Remove(&mut self, name: &str) {
+        self.0.remove(name);
+    }
+
+    pub fn IsDefined(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    pub fn Get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    pub fn IsEmpty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates the definitions sorted by name.
+    pub fn Iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+impl<K: Into<String>, V: ToString> FromIterator<(K, V)> for ShaderDefines {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.to_string()))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Value of a [`ShaderVariantKey`](super::ShaderVariantKey) entry.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ShaderKeyValue {
+    /// Defines the name without a value when `true`, for `#ifdef` blocks.
+    Bool(bool),
+
+    /// Defines the name with the value, which is also substituted in the source.
+    Int(i32),
+}
+
+impl ShaderKeyValue {
+    /// Whether both values are of the same kind.
+    pub fn IsSameKind(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::Bool(_), Self::Bool(_)) | (Self::Int(_), Self::Int(_))
+        )
+    }
+}
+
+impl From<bool> for ShaderKeyValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for ShaderKeyValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
@@ -0,0 +1,433 @@
+use super::{PreprocessedShader, ShaderDefines};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+struct Conditional {
+    is_active: bool,
+    has_else: bool,
+    line: u32,
+}
+
+/// Expands `#include`, `#define`, `#undef`, `#ifdef`, `#ifndef`, `#else` and `#endif`.
+///
+/// Includes are resolved relative to the including file first, then against the
+/// search paths, never against the working directory. Relative search paths are
+/// relative to the directory of the shader file and unused for shaders without one.
+/// Every file is included at most once, so shared structs can be included from
+/// several places.
+pub struct ShaderPreprocessor {
+    include_paths: Vec<PathBuf>,
+    /// Directory of the shader file, relative search paths are joined to it.
+    base_dir: Option<PathBuf>,
+    defines: ShaderDefines,
+    included: HashSet<PathBuf>,
+    code: String,
+    lines: Vec<(Arc<str>, u32)>,
+}
+
+impl ShaderPreprocessor {
+    pub fn New(include_paths: Vec<PathBuf>, defines: ShaderDefines) -> Self {
+        Self {
+            include_paths,
+            base_dir: None,
+            defines,
+            included: HashSet::new(),
+            code: String::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Processes `source`, errors are formatted as `file:line: message`.
+    ///
+    /// `path` is the file the source was read from, used to resolve relative includes.
+    pub fn Process(
+        mut self,
+        name: &str,
+        path: Option<&Path>,
+        source: &str,
+    ) -> Result<PreprocessedShader, String> {
+        let path = path.map(Canonicalize);
+
+        if let Some(path) = &path {
+            self.included.insert(path.clone());
+        }
+
+        self.base_dir = path
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf);
+
+        let dir = self.base_dir.clone();
+        self.ProcessFile(name.into(), dir.as_deref(), source)?;
+
+        let files = self.included.into_iter().collect();
+
+        Ok(PreprocessedShader::New(self.code, self.lines, files))
+    }
+
+    fn ProcessFile(
+        &mut self,
+        file: Arc<str>,
+        dir: Option<&Path>,
+        source: &str,
+    ) -> Result<(), String> {
+        let mut conditionals = Vec::<Conditional>::new();
+
+        for (index, text) in source.lines().enumerate() {
+            let line = index as u32 + 1;
+            let error = |message: String| format!("{}:{}: {}", file, line, message);
+            let is_active = conditionals.iter().all(|c| c.is_active);
+
+            let directive = match text.trim_start().strip_prefix('#') {
+                Some(directive) => directive,
+                None => {
+                    if is_active {
+                        let text = self.Substitute(text);
+                        self.code.push_str(&text);
+                        self.code.push('\n');
+                        self.lines.push((file.clone(), line));
+                    }
+
+                    continue;
+                }
+            };
+
+            let (keyword, argument) = match directive.trim().split_once(char::is_whitespace) {
+                Some((keyword, argument)) => (keyword, argument.trim()),
+                None => (directive.trim(), ""),
+            };
+
+            match keyword {
+                "ifdef" | "ifndef" => {
+                    if argument.is_empty() {
+                        return Err(error(format!("#{} requires a name", keyword)));
+                    }
+
+                    conditionals.push(Conditional {
+                        is_active: self.defines.IsDefined(argument) == (keyword == "ifdef"),
+                        has_else: false,
+                        line,
+                    });
+                }
+                "else" => match conditionals.last_mut() {
+                    Some(conditional) if !conditional.has_else => {
+                        conditional.is_active = !conditional.is_active;
+                        conditional.has_else = true;
+                    }
+                    Some(_) => return Err(error("duplicate #else".into())),
+                    None => return Err(error("#else without #ifdef".into())),
+                },
+                "endif" => {
+                    if conditionals.pop().is_none() {
+                        return Err(error("#endif without #ifdef".into()));
+                    }
+                }
+                _ if !is_active => {}
+                "define" => match argument.split_once(char::is_whitespace) {
+                    Some((name, value)) => self.defines.Set(name, value.trim()),
+                    None if !argument.is_empty() => self.defines.Define(argument),
+                    None => return Err(error("#define requires a name".into())),
+                },
+                "undef" => self.defines.Remove(argument),
+                "include" => {
+                    let include = argument
+                        .strip_prefix('"')
+                        .and_then(|a| a.strip_suffix('"'))
+                        .ok_or_else(|| error("expected #include \"file\"".into()))?;
+
+                    let path = self
+                        .Resolve(dir, include)
+                        .ok_or_else(|| error(format!("cannot find include {:?}", include)))?;
+
+                    let path = Canonicalize(&path);
+
+                    if self.included.insert(path.clone()) {
+                        let source = fs::read_to_string(&path)
+                            .map_err(|e| error(format!("cannot read {:?}: {}", path, e)))?;
+
+                        self.ProcessFile(
+                            path.display().to_string().into(),
+                            path.parent(),
+                            &source,
+                        )?;
+                    }
+                }
+                _ => return Err(error(format!("unknown directive #{}", keyword))),
+            }
+        }
+
+        match conditionals.last() {
+            Some(conditional) => Err(format!(
+                "{}:{}: #ifdef without #endif",
+                file, conditional.line
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn Resolve(&self, dir: Option<&Path>, include: &str) -> Option<PathBuf> {
+        let include_paths = self.include_paths.iter().filter_map(|path| {
+            if path.is_absolute() {
+                Some(path.clone())
+            } else {
+                self.base_dir.as_ref().map(|base_dir| base_dir.join(path))
+            }
+        });
+
+        dir.map(Path::to_path_buf)
+            .into_iter()
+            .chain(include_paths)
+            .map(|dir| dir.join(include))
+            .find(|path| path.is_file())
+    }
+
+    /// Replaces identifiers that have a defined value.
+    fn Substitute<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.defines.Iter().all(|(_, value)| value.is_empty()) {
+            return Cow::Borrowed(text);
+        }
+
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+
+        let is_identifier = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+        while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            let end = rest[start..]
+                .find(|c: char| !is_identifier(c))
+                .map_or(rest.len(), |end| start + end);
+
+            let word = &rest[start..end];
+
+            // Letters following digits are a literal suffix like in `2u`
+            let is_literal = rest[..start].ends_with(|c: char| c.is_ascii_digit());
+
+            result.push_str(&rest[..start]);
+
+            match self.defines.Get(word) {
+                Some(value) if !value.is_empty() && !is_literal => result.push_str(value),
+                _ => result.push_str(word),
+            }
+
+            rest = &rest[end..];
+        }
+
+        result.push_str(rest);
+
+        Cow::Owned(result)
+    }
+}
+
+fn Canonicalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Shader::Shader;
+
+    fn Process(defines: ShaderDefines, source: &str) -> Result<String, String> {
+        ShaderPreprocessor::New(Vec::new(), defines)
+            .Process("Test.wgsl", None, source)
+            .map(|shader| shader.code)
+    }
+
+    fn Lines(code: &str) -> Vec<&str> {
+        code.lines().map(str::trim).collect()
+    }
+
+    #[test]
+    fn NestedConditionals() {
+        let source = "
+            #ifdef A
+            #ifdef B
+            a_and_b
+            #else
+            a_only
+            #endif
+            #else
+            #ifndef B
+            neither
+            #endif
+            #endif
+        ";
+
+        let mut a = ShaderDefines::New();
+        a.Define("A");
+
+        let mut a_and_b = a.clone();
+        a_and_b.Define("B");
+
+        let mut b = ShaderDefines::New();
+        b.Define("B");
+
+        let Active = |defines: ShaderDefines| {
+            let code = Process(defines, source).unwrap();
+            Lines(&code)
+                .into_iter()
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(Active(a_and_b), ["a_and_b"]);
+        assert_eq!(Active(a), ["a_only"]);
+        assert_eq!(Active(b), Vec::<String>::new());
+        assert_eq!(Active(ShaderDefines::New()), ["neither"]);
+    }
+
+    #[test]
+    fn UnterminatedConditionalIsAnError() {
+        let error = Process(ShaderDefines::New(), "\n#ifdef A\n#ifdef B\n#endif\n").unwrap_err();
+
+        assert_eq!(error, "Test.wgsl:2: #ifdef without #endif");
+    }
+
+    #[test]
+    fn DuplicateElseIsAnError() {
+        let error = Process(ShaderDefines::New(), "#ifdef A\n#else\n#else\n#endif\n").unwrap_err();
+
+        assert_eq!(error, "Test.wgsl:3: duplicate #else");
+    }
+
+    #[test]
+    fn SubstitutesWholeIdentifiersOnly() {
+        let mut defines = ShaderDefines::New();
+        defines.Set("SIZE", 4);
+        defines.Set("u", "x");
+        defines.Set("f", "y");
+
+        let code = Process(
+            defines,
+            "let a = SIZE + MAX_SIZE + SIZE_X + 2u + 1.0f + u;\n",
+        )
+        .unwrap();
+
+        assert_eq!(code, "let a = 4 + MAX_SIZE + SIZE_X + 2u + 1.0f + x;\n");
+    }
+
+    #[test]
+    fn IncludesEachFileOnce() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("Main.wgsl");
+
+        fs::write(dir.path().join("Common.wgsl"), "common\n").unwrap();
+        fs::write(
+            dir.path().join("Other.wgsl"),
+            "#include \"Common.wgsl\"\nother\n",
+        )
+        .unwrap();
+
+        let source =
+            "#include \"Common.wgsl\"\n#include \"Other.wgsl\"\n#include \"Common.wgsl\"\nmain\n";
+        let shader = ShaderPreprocessor::New(Vec::new(), ShaderDefines::New())
+            .Process("Main.wgsl", Some(&main), source)
+            .unwrap();
+
+        assert_eq!(Lines(&shader.code), ["common", "other", "main"]);
+        assert_eq!(shader.files.len(), 3);
+    }
+
+    #[test]
+    fn IncludeCyclesTerminate() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("A.wgsl");
+
+        fs::write(&a, "#include \"B.wgsl\"\na\n").unwrap();
+        fs::write(dir.path().join("B.wgsl"), "#include \"A.wgsl\"\nb\n").unwrap();
+
+        let shader = Shader::FromFile(&a).unwrap().Preprocess().unwrap();
+
+        assert_eq!(Lines(&shader.code), ["b", "a"]);
+    }
+
+    #[test]
+    fn IncludesResolveAgainstIncludingFile() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("Nested");
+        let main = dir.path().join("Main.wgsl");
+
+        fs::create_dir(&nested).unwrap();
+        fs::write(
+            nested.join("Outer.wgsl"),
+            "#include \"Inner.wgsl\"\nouter\n",
+        )
+        .unwrap();
+        fs::write(nested.join("Inner.wgsl"), "inner\n").unwrap();
+        // Same name next to the main file, must not be picked for the nested include
+        fs::write(dir.path().join("Inner.wgsl"), "wrong\n").unwrap();
+
+        let shader = ShaderPreprocessor::New(Vec::new(), ShaderDefines::New())
+            .Process("Main.wgsl", Some(&main), "#include \"Nested/Outer.wgsl\"\n")
+            .unwrap();
+
+        assert_eq!(Lines(&shader.code), ["inner", "outer"]);
+    }
+
+    #[test]
+    fn RelativeIncludePathsResolveAgainstShaderFile() {
+        let dir = tempfile::tempdir().unwrap();
+        let library = dir.path().join("Library");
+        let main = dir.path().join("Main.wgsl");
+
+        fs::create_dir(&library).unwrap();
+        fs::write(library.join("Common.wgsl"), "common\n").unwrap();
+
+        let shader = ShaderPreprocessor::New(vec![PathBuf::from("Library")], ShaderDefines::New())
+            .Process("Main.wgsl", Some(&main), "#include \"Common.wgsl\"\n")
+            .unwrap();
+
+        assert_eq!(Lines(&shader.code), ["common"]);
+
+        // Without a file there is nothing to resolve against
+        let error = ShaderPreprocessor::New(vec![PathBuf::from("Library")], ShaderDefines::New())
+            .Process("Main.wgsl", None, "#include \"Common.wgsl\"\n")
+            .unwrap_err();
+
+        assert_eq!(error, "Main.wgsl:1: cannot find include \"Common.wgsl\"");
+    }
+
+    #[test]
+    fn LinesMapToIncludedFile() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("Main.wgsl");
+        let common = dir.path().join("Common.wgsl");
+
+        fs::write(
+            &common,
+            "struct A {\n    x: f32,\n};\nlet broken: f32 = ;\n",
+        )
+        .unwrap();
+        fs::write(&main, "#include \"Common.wgsl\"\n\nlet b: f32 = 1.0;\n").unwrap();
+
+        let shader = Shader::FromFile(&main).unwrap();
+        let preprocessed = shader.Preprocess().unwrap();
+        let common = fs::canonicalize(&common).unwrap().display().to_string();
+
+        assert_eq!(preprocessed.GetSourceLine(4), Some((common.as_str(), 4)));
+        assert_eq!(preprocessed.GetSourceLine(6).map(|(_, line)| line), Some(3));
+        assert_eq!(preprocessed.GetSourceLine(7), None);
+
+        let error = shader.Parse().unwrap_err().to_string();
+        assert!(error.contains(&format!("{}:4:", common)), "{}", error);
+    }
+
+    #[test]
+    fn ExampleShadersValidate() {
+        let shaders = Path::new(env!("CARGO_MANIFEST_DIR")).join("Shaders");
+
+        for name in ["Triangle.wgsl", "Cubes.wgsl"] {
+            let shader = Shader::FromFile(shaders.join(name)).unwrap();
+
+            if let Err(e) = shader.Validate() {
+                panic!("{}: {}", name, e);
+            }
+        }
+    }
+}
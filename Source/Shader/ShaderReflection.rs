@@ -0,0 +1,55 @@
+use super::{EntryPointReflection, ShaderBinding, ShaderStage};
+
+/// Interface of a shader module, see [`Shader::Reflect`](super::Shader::Reflect).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ShaderReflection {
+    pub entry_points: Vec<EntryPointReflection>,
+
+    /// Bindings used by any entry point, sorted by group and binding.
+    pub bindings: Vec<ShaderBinding>,
+}
+
+impl ShaderReflection {
+    pub fn GetEntryPoint(&self, stage: ShaderStage, name: &str) -> Option<&EntryPointReflection> {
+        self.entry_points
+            .iter()
+            .find(|entry_point| entry_point.stage == stage && entry_point.name == name)
+    }
+
+    /// Number of bind groups, including empty groups below the highest one.
+    pub fn GetGroupCount(&self) -> u32 {
+        self.bindings
+            .iter()
+            .map(|binding| binding.group + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn GetBindGroupLayoutEntries(&self, group: u32) -> Vec<wgpu::BindGroupLayoutEntry> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.group == group)
+            .map(ShaderBinding::GetLayoutEntry)
+            .collect()
+    }
+
+    /// Combines the interface of another module, used for separate vertex and fragment shaders.
+    pub fn Merge(&mut self, other: &ShaderReflection) {
+        self.entry_points.extend(other.entry_points.iter().cloned());
+
+        for binding in &other.bindings {
+            let existing = self
+                .bindings
+                .iter_mut()
+                .find(|b| b.group == binding.group && b.binding == binding.binding);
+
+            match existing {
+                Some(existing) => existing.visibility |= binding.visibility,
+                None => self.bindings.push(binding.clone()),
+            }
+        }
+
+        self.bindings
+            .sort_by_key(|binding| (binding.group, binding.binding));
+    }
+}
@@ -15,3 +15,24 @@ pub enum ShaderStage {
     /// Multi-stage shader, used with wgsl sources.
     Multiple = 3,
 }
+
+impl From<naga::ShaderStage> for ShaderStage {
+    fn from(stage: naga::ShaderStage) -> Self {
+        match stage {
+            naga::ShaderStage::Vertex => ShaderStage::Vertex,
+            naga::ShaderStage::Fragment => ShaderStage::Fragment,
+            naga::ShaderStage::Compute => ShaderStage::Compute,
+        }
+    }
+}
+
+impl From<ShaderStage> for wgpu::ShaderStages {
+    fn from(stage: ShaderStage) -> Self {
+        match stage {
+            ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+            ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+            ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+            ShaderStage::Multiple => wgpu::ShaderStages::all(),
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use super::{Shader, ShaderKeyValue, ShaderVariantKey};
+use crate::Render::{Renderer, RendererError};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Base shader compiled into one module per combination of feature keys.
+///
+/// Keys are declared with a default value and turned into preprocessor defines,
+/// so the source selects features with `#ifdef` blocks and integer substitution.
+/// Modules are cached per resolved key, requesting a permutation again is free.
+/// Pipelines are built from them with [`Renderer::GetVariantPipeline`].
+pub struct ShaderVariant {
+    shader: Shader,
+    defaults: ShaderVariantKey,
+    permutations: Vec<ShaderVariantKey>,
+    modules: Mutex<HashMap<ShaderVariantKey, Arc<wgpu::ShaderModule>>>,
+}
+
+impl ShaderVariant {
+    pub fn New(shader: Shader) -> Self {
+        Self {
+            shader,
+            defaults: ShaderVariantKey::New(),
+            permutations: Vec::new(),
+            modules: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Declares a key and the value used when a permutation doesn't set it.
+    pub fn WithKey(mut self, name: impl Into<String>, default: impl Into<ShaderKeyValue>) -> Self {
+        self.defaults.Set(name, default);
+        self
+    }
+
+    /// Declares a permutation compiled by [`ShaderVariant::Precompile`].
+    pub fn WithPermutation(mut self, key: ShaderVariantKey) -> Self {
+        self.permutations.push(key);
+        self
+    }
+
+    pub fn GetShader(&self) -> &Shader {
+        &self.shader
+    }
+
+    /// Fills in the defaults of the keys missing from `key`.
+    ///
+    /// Fails when `key` sets an undeclared key or a value of the wrong kind.
+    pub fn Resolve(&self, key: &ShaderVariantKey) -> Result<ShaderVariantKey, RendererError> {
+        let mut resolved = self.defaults.clone();
+
+        for (name, value) in key.Iter() {
+            match self.defaults.Get(name) {
+                Some(default) if default.IsSameKind(&value) => resolved.Set(name, value),
+                Some(default) => {
+                    return Err(self.KeyError(format!(
+                        "key {:?} expects {:?}, found {:?}",
+                        name, default, value
+                    )))
+                }
+                None => return Err(self.KeyError(format!("unknown key {:?}", name))),
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Returns the base shader with the defines of the permutation applied.
+    ///
+    /// The result can be used in a [`PipelineDescriptor`](crate::Render::PipelineDescriptor).
+    pub fn GetPermutation(&self, key: &ShaderVariantKey) -> Result<Shader, RendererError> {
+        let key = self.Resolve(key)?;
+        let mut shader = self.shader.clone();
+
+        key.ApplyTo(&mut shader.defines);
+
+        let features = key
+            .Iter()
+            .filter_map(|(name, value)| match value {
+                ShaderKeyValue::Bool(true) => Some(name.to_string()),
+                ShaderKeyValue::Bool(false) => None,
+                ShaderKeyValue::Int(value) => Some(format!("{}={}", name, value)),
+            })
+            .collect::<Vec<_>>();
+
+        if !features.is_empty() {
+            shader.label = format!("{} [{}]", shader.label, features.join(", "))
+                .trim_start()
+                .to_string()
+                .into();
+        }
+
+        Ok(shader)
+    }
+
+    /// Returns the module of the permutation, compiling it on first use.
+    pub fn GetModule(
+        &self,
+        renderer: &Renderer,
+        key: &ShaderVariantKey,
+    ) -> Result<Arc<wgpu::ShaderModule>, RendererError> {
+        let resolved = self.Resolve(key)?;
+
+        if let Some(module) = self.modules.lock().unwrap().get(&resolved) {
+            return Ok(module.clone());
+        }
+
+        // Compile without holding the lock, other permutations stay available meanwhile
+        let module = Arc::new(renderer.SubmitShader(&self.GetPermutation(&resolved)?)?);
+
+        Ok(self
+            .modules
+            .lock()
+            .unwrap()
+            .entry(resolved)
+            .or_insert(module)
+            .clone())
+    }
+
+    /// Compiles every declared permutation, call at startup to avoid hitches on first use.
+    pub fn Precompile(&self, renderer: &Renderer) -> Result<(), RendererError> {
+        for key in &self.permutations {
+            self.GetModule(renderer, key)?;
+        }
+
+        Ok(())
+    }
+
+    /// Number of compiled permutations.
+    pub fn GetModuleCount(&self) -> usize {
+        self.modules.lock().unwrap().len()
+    }
+
+    /// Reads the base shader again when one of its files changed and drops the compiled
+    /// modules, returns whether it did.
+    ///
+    /// The modules are kept when the shader fails to load.
+    pub fn Reload(&mut self, changed: &[PathBuf]) -> Result<bool, RendererError> {
+        if !self
+            .shader
+            .GetFiles()
+            .iter()
+            .any(|file| changed.contains(file))
+        {
+            return Ok(false);
+        }
+
+        self.shader.Reload()?;
+        self.Clear();
+
+        Ok(true)
+    }
+
+    /// Drops the compiled modules, required after the device was recreated.
+    pub fn Clear(&self) {
+        self.modules.lock().unwrap().clear();
+    }
+
+    fn KeyError(&self, message: String) -> RendererError {
+        RendererError::ShaderCompilation {
+            label: self.shader.label.to_string(),
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Render::PipelineDescriptor;
+    use std::fs;
+
+    const SHADER: &str = "
+        @vertex
+        fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+            return vec4<f32>(f32(index), 0.0, 0.0, 1.0);
+        }
+
+        @fragment
+        fn fs_main() -> @location(0) vec4<f32> {
+        #ifdef RED
+            return vec4<f32>(1.0, 0.0, 0.0, f32(SAMPLES));
+        #else
+            return vec4<f32>(1.0, 1.0, 1.0, f32(SAMPLES));
+        #endif
+        }
+    ";
+
+    fn Variant(shader: Shader) -> ShaderVariant {
+        ShaderVariant::New(Shader {
+            label: "Variant".into(),
+            ..shader
+        })
+        .WithKey("RED", false)
+        .WithKey("SAMPLES", 1)
+    }
+
+    #[test]
+    fn ResolveFillsDefaults() {
+        let variant = Variant(Shader::FromWgsl(SHADER));
+
+        assert_eq!(
+            variant.Resolve(&ShaderVariantKey::New()).unwrap(),
+            ShaderVariantKey::New()
+                .With("RED", false)
+                .With("SAMPLES", 1)
+        );
+        assert_eq!(
+            variant
+                .Resolve(&ShaderVariantKey::New().With("SAMPLES", 4))
+                .unwrap(),
+            ShaderVariantKey::New()
+                .With("RED", false)
+                .With("SAMPLES", 4)
+        );
+    }
+
+    #[test]
+    fn ResolveRejectsUnknownKeys() {
+        let variant = Variant(Shader::FromWgsl(SHADER));
+
+        let error = variant
+            .Resolve(&ShaderVariantKey::New().With("BLUE", true))
+            .unwrap_err();
+
+        assert!(
+            matches!(&error, RendererError::ShaderCompilation { label, message }
+                if label == "Variant" && message == "unknown key \"BLUE\""),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn ResolveRejectsWrongKind() {
+        let variant = Variant(Shader::FromWgsl(SHADER));
+
+        assert!(variant
+            .Resolve(&ShaderVariantKey::New().With("RED", 1))
+            .is_err());
+        assert!(variant
+            .Resolve(&ShaderVariantKey::New().With("SAMPLES", true))
+            .is_err());
+    }
+
+    #[test]
+    fn PermutationAppliesDefines() {
+        let variant = Variant(Shader::FromWgsl(SHADER));
+
+        let default = variant.GetPermutation(&ShaderVariantKey::New()).unwrap();
+        assert_eq!(
+            default.defines.Iter().collect::<Vec<_>>(),
+            [("SAMPLES", "1")]
+        );
+        assert_eq!(default.label, "Variant [SAMPLES=1]");
+
+        let red = variant
+            .GetPermutation(&ShaderVariantKey::New().With("RED", true).With("SAMPLES", 4))
+            .unwrap();
+        assert_eq!(
+            red.defines.Iter().collect::<Vec<_>>(),
+            [("RED", ""), ("SAMPLES", "4")]
+        );
+        assert_eq!(red.label, "Variant [RED, SAMPLES=4]");
+
+        let code = red.Preprocess().unwrap().code;
+        assert!(
+            code.contains("vec4<f32>(1.0, 0.0, 0.0, f32(4))"),
+            "{}",
+            code
+        );
+        assert!(!code.contains("1.0, 1.0, 1.0"), "{}", code);
+    }
+
+    #[test]
+    fn ReloadOnlyForOwnFiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Variant.wgsl");
+        fs::write(&file, SHADER).unwrap();
+
+        let mut variant = Variant(Shader::FromFile(&file).unwrap());
+
+        assert!(!variant.Reload(&[dir.path().join("Other.wgsl")]).unwrap());
+
+        fs::write(&file, SHADER.replace("1.0, 1.0, 1.0", "0.5, 0.5, 0.5")).unwrap();
+
+        assert!(variant.Reload(&variant.GetShader().GetFiles()).unwrap());
+
+        let code = variant
+            .GetPermutation(&ShaderVariantKey::New())
+            .unwrap()
+            .Preprocess()
+            .unwrap()
+            .code;
+        assert!(code.contains("0.5, 0.5, 0.5"), "{}", code);
+    }
+
+    #[test]
+    #[ignore = "needs an adapter"]
+    fn ReloadDropsModules() {
+        let renderer = Renderer::NewForTests();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Variant.wgsl");
+        fs::write(&file, SHADER).unwrap();
+
+        let mut variant = Variant(Shader::FromFile(&file).unwrap());
+        let key = ShaderVariantKey::New();
+        let previous = variant.GetModule(&renderer, &key).unwrap();
+
+        assert!(Arc::ptr_eq(
+            &previous,
+            &variant.GetModule(&renderer, &key).unwrap()
+        ));
+        assert!(variant.Reload(&variant.GetShader().GetFiles()).unwrap());
+        assert_eq!(variant.GetModuleCount(), 0);
+        assert!(!Arc::ptr_eq(
+            &previous,
+            &variant.GetModule(&renderer, &key).unwrap()
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs an adapter"]
+    fn VariantPipelinesAreCachedPerPermutation() {
+        let renderer = Renderer::NewForTests();
+
+        let variant = Variant(Shader::FromWgsl(SHADER));
+        let descriptor = PipelineDescriptor::New(Shader::FromWgsl(""));
+        let red = ShaderVariantKey::New().With("RED", true);
+
+        for key in [&ShaderVariantKey::New(), &red, &red] {
+            renderer
+                .GetVariantPipeline(&descriptor, &variant, key, &[])
+                .unwrap();
+        }
+
+        let stats = renderer.pipeline_cache.GetStats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+        assert_eq!(variant.GetModuleCount(), 2);
+    }
+}
@@ -0,0 +1,58 @@
+use super::{ShaderDefines, ShaderKeyValue};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Set of named feature values selecting one permutation of a [`ShaderVariant`](super::ShaderVariant).
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ShaderVariantKey(BTreeMap<String, ShaderKeyValue>);
+
+impl ShaderVariantKey {
+    pub fn New() -> Self {
+        Self::default()
+    }
+
+    /// Builder form of [`ShaderVariantKey::Set`].
+    pub fn With(mut self, name: impl Into<String>, value: impl Into<ShaderKeyValue>) -> Self {
+        self.Set(name, value);
+        self
+    }
+
+    pub fn Set(&mut self, name: impl Into<String>, value: impl Into<ShaderKeyValue>) {
+        self.0.insert(name.into(), value.into());
+    }
+
+    pub fn Get(&self, name: &str) -> Option<ShaderKeyValue> {
+        self.0.get(name).copied()
+    }
+
+    pub fn IsEmpty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates the values sorted by name.
+    pub fn Iter(&self) -> impl Iterator<Item = (&str, ShaderKeyValue)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), *v))
+    }
+
+    /// Adds the values to `defines`, `false` booleans remove the name.
+    pub fn ApplyTo(&self, defines: &mut ShaderDefines) {
+        for (name, value) in self.Iter() {
+            match value {
+                ShaderKeyValue::Bool(true) => defines.Define(name),
+                ShaderKeyValue::Bool(false) => defines.Remove(name),
+                ShaderKeyValue::Int(value) => defines.Set(name, value),
+            }
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<ShaderKeyValue>> FromIterator<(K, V)> for ShaderVariantKey {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Polls shader files for changes by comparing their modification time.
+///
+/// Used by the runtime to hot reload pipelines, see [`RuntimeConfig::hot_reload`](crate::RuntimeConfig::hot_reload).
+pub struct ShaderWatcher {
+    files: HashMap<PathBuf, Option<SystemTime>>,
+    interval: Duration,
+    last_poll: Option<Instant>,
+}
+
+impl ShaderWatcher {
+    /// Creates a watcher checking the files at most once per `interval`.
+    pub fn New(interval: Duration) -> Self {
+        Self {
+            files: HashMap::new(),
+            interval,
+            last_poll: None,
+        }
+    }
+
+    /// Returns the files modified since the previous poll.
+    ///
+    /// Files seen for the first time are only recorded, files no longer in `files` are forgotten.
+    pub fn Poll(&mut self, files: &[PathBuf]) -> Vec<PathBuf> {
+        let now = Instant::now();
+
+        if self
+            .last_poll
+            .is_some_and(|last| now - last < self.interval)
+        {
+            return Vec::new();
+        }
+
+        self.last_poll = Some(now);
+        self.files.retain(|file, _| files.contains(file));
+
+        let mut changed = Vec::new();
+
+        for file in files {
+            let modified = Self::GetModified(file);
+
+            match self.files.get_mut(file) {
+                // Editors may replace the file, skip the moment it does not exist
+                Some(last) if modified.is_some() && *last != modified => {
+                    *last = modified;
+                    changed.push(file.clone());
+                }
+                Some(_) => {}
+                None => {
+                    self.files.insert(file.clone(), modified);
+                }
+            }
+        }
+
+        changed
+    }
+
+    fn GetModified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sets the modification time explicitly, file systems may not resolve two quick writes.
+    fn Touch(path: &Path, seconds: u64) {
+        fs::write(path, "").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    #[test]
+    fn ReportsModifiedFileOnce() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Shader.wgsl");
+        let files = [file.clone()];
+        let mut watcher = ShaderWatcher::New(Duration::ZERO);
+
+        Touch(&file, 1000);
+        assert!(watcher.Poll(&files).is_empty());
+
+        Touch(&file, 2000);
+        assert_eq!(watcher.Poll(&files), files);
+        assert!(watcher.Poll(&files).is_empty());
+    }
+
+    #[test]
+    fn SkipsFileWhileMissing() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Shader.wgsl");
+        let files = [file.clone()];
+        let mut watcher = ShaderWatcher::New(Duration::ZERO);
+
+        Touch(&file, 1000);
+        watcher.Poll(&files);
+
+        fs::remove_file(&file).unwrap();
+        assert!(watcher.Poll(&files).is_empty());
+
+        Touch(&file, 2000);
+        assert_eq!(watcher.Poll(&files), files);
+    }
+
+    #[test]
+    fn ForgetsRemovedFiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Shader.wgsl");
+        let files = [file.clone()];
+        let mut watcher = ShaderWatcher::New(Duration::ZERO);
+
+        Touch(&file, 1000);
+        watcher.Poll(&files);
+        watcher.Poll(&[]);
+
+        // Seen for the first time again, only recorded
+        Touch(&file, 2000);
+        assert!(watcher.Poll(&files).is_empty());
+    }
+
+    #[test]
+    fn PollsAtMostOncePerInterval() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Shader.wgsl");
+        let files = [file.clone()];
+        let mut watcher = ShaderWatcher::New(Duration::from_secs(3600));
+
+        Touch(&file, 1000);
+        watcher.Poll(&files);
+
+        Touch(&file, 2000);
+        assert!(watcher.Poll(&files).is_empty());
+    }
+}
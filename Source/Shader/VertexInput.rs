@@ -0,0 +1,13 @@
+use crate::Render::VertexFormat;
+use serde::{Deserialize, Serialize};
+
+/// Input of a vertex entry point bound to a `@location`.
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VertexInput {
+    /// Argument or struct member name in the shader.
+    pub name: String,
+    pub location: u32,
+
+    /// 32 or 64 bit format of the shader type, `vec3<f32>` is `Float32x3`.
+    pub format: VertexFormat,
+}
@@ -1,7 +1,35 @@
+#[path = "EntryPointReflection.rs"]
+mod _EntryPointReflection;
+pub use self::_EntryPointReflection::*;
+
+#[path = "PreprocessedShader.rs"]
+mod _PreprocessedShader;
+pub use self::_PreprocessedShader::*;
+
 #[path = "Shader.rs"]
 mod _Shader;
 pub use self::_Shader::*;
 
+#[path = "ShaderBinding.rs"]
+mod _ShaderBinding;
+pub use self::_ShaderBinding::*;
+
+#[path = "ShaderDefines.rs"]
+mod _ShaderDefines;
+pub use self::_ShaderDefines::*;
+
+#[path = "ShaderKeyValue.rs"]
+mod _ShaderKeyValue;
+pub use self::_ShaderKeyValue::*;
+
+#[path = "ShaderPreprocessor.rs"]
+mod _ShaderPreprocessor;
+pub use self::_ShaderPreprocessor::*;
+
+#[path = "ShaderReflection.rs"]
+mod _ShaderReflection;
+pub use self::_ShaderReflection::*;
+
 #[path = "ShaderSource.rs"]
 mod _ShaderSource;
 pub use self::_ShaderSource::*;
@@ -9,3 +37,19 @@ pub use self::_ShaderSource::*;
 #[path = "ShaderStage.rs"]
 mod _ShaderStage;
 pub use self::_ShaderStage::*;
+
+#[path = "ShaderVariant.rs"]
+mod _ShaderVariant;
+pub use self::_ShaderVariant::*;
+
+#[path = "ShaderVariantKey.rs"]
+mod _ShaderVariantKey;
+pub use self::_ShaderVariantKey::*;
+
+#[path = "ShaderWatcher.rs"]
+mod _ShaderWatcher;
+pub use self::_ShaderWatcher::*;
+
+#[path = "VertexInput.rs"]
+mod _VertexInput;
+pub use self::_VertexInput::*;
@@ -1,5 +1,7 @@
-use crate::Render::Renderer;
+use crate::ErrorAction;
+use crate::Render::{Renderer, RendererError};
 use anyhow::Result;
+use std::path::PathBuf;
 use std::time::Duration;
 use winit::event::*;
 
@@ -9,5 +11,32 @@ pub trait State: Sized + 'static {
     fn Input(&mut self, renderer: &Renderer, event: &WindowEvent) -> bool;
     fn Update(&mut self, renderer: &Renderer, delta: Duration);
     fn Resize(&mut self, renderer: &Renderer);
-    fn Draw(&mut self, renderer: &mut Renderer) -> Result<(), wgpu::SurfaceError>;
+    fn Draw(&mut self, renderer: &mut Renderer) -> Result<(), RendererError>;
+
+    /// Called zero or more times per frame with a constant `step` when the runtime
+    /// is configured with a fixed timestep, before [`State::Update`].
+    fn FixedUpdate(&mut self, _renderer: &Renderer, _step: Duration) {}
+
+    /// Draws the frame, `alpha` is the progress in `[0, 1)` from the last fixed update
+    /// towards the next one, used to interpolate between simulation states.
+    ///
+    /// Without a fixed timestep `alpha` is always `1.0`.
+    fn DrawInterpolated(
+        &mut self,
+        renderer: &mut Renderer,
+        _alpha: f32,
+    ) -> Result<(), RendererError> {
+        self.Draw(renderer)
+    }
+
+    /// Called with the changed shader files after the cached pipelines were rebuilt,
+    /// when hot reload is enabled. Reload the [`ShaderVariant`](crate::Shader::ShaderVariant)s
+    /// of the state here.
+    fn ReloadShaders(&mut self, _renderer: &Renderer, _changed: &[PathBuf]) {}
+
+    /// Decides how the runtime recovers from an error returned by [`State::Draw`]
+    /// or raised by the device.
+    fn OnError(&mut self, _renderer: &mut Renderer, error: &RendererError) -> ErrorAction {
+        ErrorAction::FromError(error)
+    }
 }
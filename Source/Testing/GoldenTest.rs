@@ -0,0 +1,124 @@
+use super::{ImageComparison, Tolerance};
+use crate::Render::{Renderer, RendererConfig};
+use crate::State;
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Renders a [`State`] headless and compares the result against a reference image.
+///
+/// ```ignore
+/// let test = GoldenTest::New(256, 256);
+/// test.Run::<Triangle, _>("Tests/Golden/Triangle.png")?;
+/// ```
+///
+/// Missing references fail the test, run with `UPDATE_GOLDEN=1` to (re)create them.
+/// Without any adapter rendering fails with [`RendererError::NoAdapter`](crate::Render::RendererError::NoAdapter).
+pub struct GoldenTest {
+    pub width: u32,
+    pub height: u32,
+
+    /// Number of update and draw calls before capturing.
+    pub frames: u32,
+
+    /// Delta passed to every fixed and variable update, constant to keep the output deterministic.
+    pub delta: Duration,
+
+    pub tolerance: Tolerance,
+
+    /// Adapter selection, defaults to the fallback adapter the references are rendered with.
+    pub renderer_config: RendererConfig,
+
+    /// Directory receiving the actual and diff images of failed comparisons.
+    pub output_dir: PathBuf,
+}
+
+impl GoldenTest {
+    pub const UPDATE_ENV: &'static str = "UPDATE_GOLDEN";
+
+    pub fn New(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            frames: 1,
+            delta: Duration::from_secs_f64(1.0 / 60.0),
+            tolerance: Tolerance::default(),
+            renderer_config: RendererConfig::FallbackFromEnv(),
+            output_dir: std::env::temp_dir().join("GoldenTests"),
+        }
+    }
+
+    /// Drives the state for the configured number of frames and captures the result.
+    pub fn Render<T: State>(&self) -> Result<image::RgbaImage> {
+        let mut renderer = pollster::block_on(Renderer::NewHeadless(
+            self.width,
+            self.height,
+            &self.renderer_config,
+        ))?;
+
+        let mut app = T::Init(&renderer)?;
+
+        for frame in 0..self.frames {
+            renderer.frame_stats.Record(self.delta);
+            app.FixedUpdate(&renderer, self.delta);
+            app.Update(&renderer, self.delta);
+            app.DrawInterpolated(&mut renderer, 1.0)
+                .with_context(|| format!("Failed to draw frame {}", frame))?;
+        }
+
+        renderer.CaptureFrame()
+    }
+
+    /// Renders the state and compares it with the reference image at `reference`.
+    pub fn Run<T: State, P: AsRef<Path>>(&self, reference: P) -> Result<()> {
+        let reference = reference.as_ref();
+        let actual = self.Render::<T>()?;
+
+        if std::env::var_os(Self::UPDATE_ENV).is_some() {
+            if let Some(parent) = reference.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            actual.save(reference)?;
+            log::info!("Updated reference image {:?}", reference);
+
+            return Ok(());
+        }
+
+        if !reference.exists() {
+            bail!(
+                "Reference image {:?} does not exist, run with {}=1 to create it",
+                reference,
+                Self::UPDATE_ENV
+            );
+        }
+
+        let expected = image::open(reference)?.to_rgba8();
+        let comparison = ImageComparison::New(&actual, &expected, &self.tolerance)?;
+
+        if comparison.IsWithin() {
+            return Ok(());
+        }
+
+        let name = reference
+            .file_stem()
+            .map_or_else(|| "Golden".into(), |stem| stem.to_string_lossy());
+
+        std::fs::create_dir_all(&self.output_dir)?;
+
+        let actual_path = self.output_dir.join(format!("{}.actual.png", name));
+        let diff_path = self.output_dir.join(format!("{}.diff.png", name));
+
+        actual.save(&actual_path)?;
+        comparison.diff.save(&diff_path)?;
+
+        bail!(
+            "{} pixels differ from {:?} (max channel difference {}), see {:?} and {:?}",
+            comparison.failing_pixels,
+            reference,
+            comparison.max_difference,
+            actual_path,
+            diff_path
+        )
+    }
+}
@@ -0,0 +1,160 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Allowed deviation between a rendered image and its reference.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Tolerance {
+    /// Maximum absolute difference of any channel before a pixel counts as different.
+    pub per_channel: u8,
+
+    /// Number of differing pixels accepted before the comparison fails.
+    pub max_failing_pixels: usize,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            per_channel: 2,
+            max_failing_pixels: 0,
+        }
+    }
+}
+
+/// Result of comparing two images pixel by pixel.
+pub struct ImageComparison {
+    pub tolerance: Tolerance,
+    pub failing_pixels: usize,
+    pub max_difference: u8,
+    /// Differing pixels in red over a dimmed copy of the reference.
+    pub diff: image::RgbaImage,
+}
+
+impl ImageComparison {
+    pub fn New(
+        actual: &image::RgbaImage,
+        expected: &image::RgbaImage,
+        tolerance: &Tolerance,
+    ) -> Result<Self> {
+        if actual.dimensions() != expected.dimensions() {
+            bail!(
+                "Image size {:?} does not match reference size {:?}",
+                actual.dimensions(),
+                expected.dimensions()
+            );
+        }
+
+        let mut failing_pixels = 0;
+        let mut max_difference = 0;
+        let mut diff = image::RgbaImage::new(actual.width(), actual.height());
+
+        for ((a, e), d) in actual
+            .pixels()
+            .zip(expected.pixels())
+            .zip(diff.pixels_mut())
+        {
+            let difference =
+                a.0.iter()
+                    .zip(e.0.iter())
+                    .map(|(a, e)| a.abs_diff(*e))
+                    .max()
+                    .unwrap_or(0);
+
+            max_difference = max_difference.max(difference);
+
+            if difference > tolerance.per_channel {
+                failing_pixels += 1;
+                *d = image::Rgba([255, 0, 0, 255]);
+            } else {
+                let luma = (e.0[0] as u32 + e.0[1] as u32 + e.0[2] as u32) / 3;
+                let dimmed = (luma / 4) as u8;
+                *d = image::Rgba([dimmed, dimmed, dimmed, 255]);
+            }
+        }
+
+        Ok(Self {
+            tolerance: *tolerance,
+            failing_pixels,
+            max_difference,
+            diff,
+        })
+    }
+
+    /// Whether few enough pixels differ for the images to count as equal.
+    pub fn IsWithin(&self) -> bool {
+        self.failing_pixels <= self.tolerance.max_failing_pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: image::Rgba<u8> = image::Rgba([255, 0, 0, 255]);
+
+    fn Gray(value: u8) -> image::RgbaImage {
+        image::RgbaImage::from_pixel(4, 4, image::Rgba([value, value, value, 255]))
+    }
+
+    #[test]
+    fn IdenticalImagesMatch() {
+        let comparison =
+            ImageComparison::New(&Gray(100), &Gray(100), &Tolerance::default()).unwrap();
+
+        assert_eq!(comparison.failing_pixels, 0);
+        assert_eq!(comparison.max_difference, 0);
+        assert!(comparison.IsWithin());
+        assert!(comparison.diff.pixels().all(|&pixel| pixel != RED));
+    }
+
+    #[test]
+    fn DifferencesWithinPerChannelPass() {
+        let comparison =
+            ImageComparison::New(&Gray(102), &Gray(100), &Tolerance::default()).unwrap();
+
+        assert_eq!(comparison.failing_pixels, 0);
+        assert_eq!(comparison.max_difference, 2);
+        assert!(comparison.IsWithin());
+    }
+
+    #[test]
+    fn DifferingPixelsAreCountedAndMarked() {
+        let mut actual = Gray(100);
+        actual.put_pixel(1, 2, image::Rgba([100, 130, 100, 255]));
+        actual.put_pixel(3, 0, image::Rgba([100, 100, 100, 0]));
+
+        let tolerance = Tolerance {
+            per_channel: 2,
+            max_failing_pixels: 1,
+        };
+
+        let comparison = ImageComparison::New(&actual, &Gray(100), &tolerance).unwrap();
+
+        assert_eq!(comparison.failing_pixels, 2);
+        assert_eq!(comparison.max_difference, 255);
+        assert!(!comparison.IsWithin());
+        assert_eq!(*comparison.diff.get_pixel(1, 2), RED);
+        assert_eq!(*comparison.diff.get_pixel(3, 0), RED);
+        assert_ne!(*comparison.diff.get_pixel(0, 0), RED);
+    }
+
+    #[test]
+    fn FailingPixelsUpToTheLimitPass() {
+        let mut actual = Gray(100);
+        actual.put_pixel(0, 0, image::Rgba([0, 0, 0, 255]));
+
+        let tolerance = Tolerance {
+            per_channel: 0,
+            max_failing_pixels: 1,
+        };
+
+        assert!(ImageComparison::New(&actual, &Gray(100), &tolerance)
+            .unwrap()
+            .IsWithin());
+    }
+
+    #[test]
+    fn SizeMismatchFails() {
+        let small = image::RgbaImage::new(2, 2);
+        assert!(ImageComparison::New(&small, &Gray(0), &Tolerance::default()).is_err());
+    }
+}
@@ -0,0 +1,7 @@
+#[path = "GoldenTest.rs"]
+mod _GoldenTest;
+pub use self::_GoldenTest::*;
+
+#[path = "ImageComparison.rs"]
+mod _ImageComparison;
+pub use self::_ImageComparison::*;
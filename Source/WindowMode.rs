@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use winit::monitor::MonitorHandle;
+use winit::window::Fullscreen;
+
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum WindowMode {
+    /// Regular decorated window.
+    Windowed,
+
+    /// Borderless window covering the monitor at the given index, `None` for the current one.
+    Borderless { monitor: Option<usize> },
+
+    /// Exclusive fullscreen using the largest video mode of the monitor at the given index,
+    /// `None` for the primary one.
+    Exclusive { monitor: Option<usize> },
+}
+
+impl Default for WindowMode {
+    fn default() -> Self {
+        Self::Windowed
+    }
+}
+
+impl WindowMode {
+    /// Resolves the mode into a winit fullscreen state.
+    ///
+    /// Falls back to borderless when the requested monitor or video mode is not available.
+    pub fn ToFullscreen(
+        &self,
+        monitors: impl IntoIterator<Item = MonitorHandle>,
+        primary: Option<MonitorHandle>,
+    ) -> Option<Fullscreen> {
+        let mut monitors = monitors.into_iter();
+
+        match *self {
+            Self::Windowed => None,
+            Self::Borderless { monitor: None } => Some(Fullscreen::Borderless(None)),
+            Self::Borderless {
+                monitor: Some(index),
+            } => Some(Fullscreen::Borderless(monitors.nth(index))),
+            Self::Exclusive { monitor } => {
+                let monitor = match monitor {
+                    Some(index) => monitors.nth(index),
+                    None => primary.or_else(|| monitors.next()),
+                };
+
+                let video_mode = monitor.as_ref().and_then(|monitor| {
+                    monitor.video_modes().max_by_key(|mode| {
+                        let size = mode.size();
+                        (
+                            size.width * size.height,
+                            mode.refresh_rate(),
+                            mode.bit_depth(),
+                        )
+                    })
+                });
+
+                match video_mode {
+                    Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                    None => {
+                        log::warn!("No exclusive video mode available, using borderless");
+                        Some(Fullscreen::Borderless(monitor))
+                    }
+                }
+            }
+        }
+    }
+}
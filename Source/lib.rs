@@ -1,15 +1,43 @@
 #![allow(non_snake_case)]
 #![allow(unused_imports)]
 
+// Lets the derive macros refer to `::Graphics` from inside this crate too
+extern crate self as Graphics;
+
 pub mod Camera;
 pub mod Color;
 pub mod Render;
 pub mod Shader;
+pub mod Testing;
+
+#[path = "ErrorAction.rs"]
+mod _ErrorAction;
+pub use self::_ErrorAction::*;
+
+#[path = "FixedTimestep.rs"]
+mod _FixedTimestep;
+pub use self::_FixedTimestep::*;
+
+#[path = "FrameLimiter.rs"]
+mod _FrameLimiter;
+pub use self::_FrameLimiter::*;
+
+#[path = "FrameStats.rs"]
+mod _FrameStats;
+pub use self::_FrameStats::*;
 
 #[path = "Runtime.rs"]
 mod _Runtime;
 pub use self::_Runtime::*;
 
+#[path = "RuntimeConfig.rs"]
+mod _RuntimeConfig;
+pub use self::_RuntimeConfig::*;
+
 #[path = "State.rs"]
 mod _State;
 pub use self::_State::*;
+
+#[path = "WindowMode.rs"]
+mod _WindowMode;
+pub use self::_WindowMode::*;
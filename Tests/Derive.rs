@@ -0,0 +1,8 @@
+#![allow(non_snake_case)]
+
+/// Misuses of `#[derive(Vertex)]` must fail with a readable error, see the `.stderr` files.
+#[test]
+fn CompileErrors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("Tests/Derive/*.rs");
+}
@@ -0,0 +1,10 @@
+use Graphics::Render::Vertex;
+
+#[repr(C)]
+#[derive(Copy, Clone, Vertex)]
+struct BadFormat {
+    #[vertex(format = Float33)]
+    position: [f32; 3],
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use Graphics::Render::Vertex;
+
+#[repr(C)]
+#[derive(Copy, Clone, Vertex)]
+struct Generic<T> {
+    position: [T; 3],
+}
+
+fn main() {}
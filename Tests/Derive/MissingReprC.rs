@@ -0,0 +1,8 @@
+use Graphics::Render::Vertex;
+
+#[derive(Copy, Clone, Vertex)]
+struct MissingReprC {
+    position: [f32; 3],
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use Graphics::Render::Vertex;
+
+#[repr(C)]
+#[derive(Copy, Clone, Vertex)]
+struct UnsupportedField {
+    position: [f32; 3],
+    flag: bool,
+}
+
+fn main() {}
@@ -0,0 +1,43 @@
+#![allow(non_snake_case)]
+#![allow(unused_variables)]
+
+#[path = "../Examples/Common/Cubes.rs"]
+mod _Cubes;
+use self::_Cubes::*;
+
+#[path = "../Examples/Common/Triangle.rs"]
+mod _Triangle;
+use self::_Triangle::*;
+
+use std::path::PathBuf;
+use Graphics::State;
+use Graphics::Testing::{GoldenTest, Tolerance};
+
+/// Runs the golden test against `Tests/Golden/<name>.png`.
+fn Check<T: State>(test: GoldenTest, name: &str) {
+    let reference = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("Tests/Golden")
+        .join(format!("{}.png", name));
+
+    test.Run::<T, _>(&reference).unwrap();
+}
+
+#[test]
+#[ignore = "needs an adapter"]
+fn Triangle() {
+    Check::<Triangle>(GoldenTest::New(256, 256), "Triangle");
+}
+
+#[test]
+#[ignore = "needs an adapter"]
+fn Cubes() {
+    let mut test = GoldenTest::New(256, 256);
+
+    // Rasterizers may disagree on pixels whose center lies right on a cube edge
+    test.tolerance = Tolerance {
+        per_channel: 2,
+        max_failing_pixels: 64,
+    };
+
+    Check::<Cubes>(test, "Cubes");
+}